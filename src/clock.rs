@@ -1,110 +1,993 @@
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use bevy::prelude::*;
+use bevy::{prelude::*, time::Stopwatch};
+use chrono::{DateTime, Local, NaiveTime, TimeDelta};
+pub use format::{ClockFormat, ClockFraction, ClockUnits};
+pub use multiplier::{MultiplierKind, MultiplierWindow, ScheduleMultiplier};
+pub use popup::TimePopups;
+use serde::{Deserialize, Serialize};
+pub use urgency::{ClockUrgency, UrgencyStyle};
 
-const SECONDS_IN_HOUR: u32 = 3600;
-const SECONDS_IN_MINUTE: u32 = 60;
+use crate::{draggable_interface::crisp_text::CrispText, particles::fireworks::CreateFireworks};
+
+mod format;
+mod multiplier;
+pub mod persistence;
+mod popup;
+pub mod subathon;
+mod urgency;
+
+/// How long a paused clock spends shown, then hidden, while it blinks.
+const PAUSED_BLINK_SECONDS: f32 = 0.5;
+/// The most a clock can hold, about ten years, so time added from chat or
+/// donations can't overflow it.
+const MAX_CLOCK_SECONDS: f32 = 315_360_000.0;
 
 #[derive(Event)]
 pub struct AddTime {
   seconds: f32,
+  target: ClockTarget,
+  multiplied: bool,
 }
 
-#[derive(Component)]
-pub struct MakeClock(pub Clock);
+/// Stops clocks counting until they are resumed.
+#[derive(Event, Default)]
+pub struct PauseClock {
+  target: ClockTarget,
+}
+
+#[derive(Event, Default)]
+pub struct ResumeClock {
+  target: ClockTarget,
+}
+
+/// Starts clocks counting down again from the given seconds, ending any
+/// overtime or stopwatch mode they were in.
+#[derive(Event)]
+pub struct ResetClock {
+  seconds: f32,
+  target: ClockTarget,
+}
+
+/// Changes the time clocks show without changing what they count.
+#[derive(Event)]
+pub struct SetClock {
+  seconds: f32,
+  target: ClockTarget,
+}
+
+/// Which clocks an event applies to.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum ClockTarget {
+  #[default]
+  All,
+  /// The clock entity, or the widget it was made on.
+  Entity(Entity),
+  Id(ClockId),
+}
+
+/// Names a clock so events can be aimed at it.
+#[derive(Component, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ClockId(pub String);
 
+/// Spawns the given clock, in whichever mode it was made with, as a child of
+/// this entity.
 #[derive(Component)]
+pub struct MakeClock {
+  clock: Clock,
+  id: Option<ClockId>,
+  popups: Option<TimePopups>,
+  urgency: Option<ClockUrgency>,
+}
+
+/// Triggered once each time a clock runs out. Adding time afterwards
+/// restarts it, so it can finish again.
+#[derive(Event, Clone, Copy)]
+pub struct ClockFinished {
+  pub entity: Entity,
+}
+
+/// Triggered when a clock that had finished has time again.
+#[derive(Event, Clone, Copy)]
+pub struct ClockRestarted {
+  pub entity: Entity,
+}
+
+/// Triggered when `AddTime` changes how long a countdown has left, with the
+/// seconds actually added after clamping, negative if time was taken off.
+#[derive(Event, Clone, Copy)]
+pub struct ClockTimeAdded {
+  pub entity: Entity,
+  pub seconds: f32,
+}
+
+/// A chat message from a clock's `ClockFinishedAction::ChatMessage`, for
+/// whichever chat the app is connected to.
+#[derive(Event, Clone)]
+pub struct ClockChatMessage(pub String);
+
+/// Something a clock does when it runs out.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClockFinishedAction {
+  /// Sets off fireworks for this many seconds.
+  Fireworks(f32),
+  /// Hides the clock, along with the widget it sits on, until it restarts.
+  Hide,
+  ChatMessage(String),
+  /// Carries on counting up from zero as a stopwatch until time is added.
+  Overtime,
+}
+
+#[derive(Component, Clone)]
 pub struct Clock {
+  mode: ClockMode,
+  format: ClockFormat,
   duration: Timer,
+  elapsed: Stopwatch,
+  on_finished: Vec<ClockFinishedAction>,
+  finished: bool,
+  overtime: bool,
+  /// When the clock was paused, wall clock countdowns are moved on by how
+  /// long they were paused for once resumed.
+  paused_at: Option<DateTime<Local>>,
+  real_time: bool,
+  /// When a real time clock last caught up with the system clock.
+  synced_at: Option<Instant>,
+  multipliers: Vec<(MultiplierKind, MultiplierWindow)>,
+}
+
+type TargetedClocks<'w, 's> = Query<
+  'w,
+  's,
+  (
+    Entity,
+    &'static mut Clock,
+    Option<&'static ClockId>,
+    Option<&'static ChildOf>,
+  ),
+>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ClockMode {
+  /// Counts down to zero, added time makes it longer.
+  Countdown,
+  /// Counts up from zero, such as for how long the stream has been live.
+  Stopwatch,
+  /// Counts down to a moment on the local wall clock, such as when the
+  /// stream starts.
+  CountdownTo(DateTime<Local>),
 }
 
 pub(super) fn plugin(app: &mut App) {
   app
+    .add_plugins((
+      multiplier::plugin,
+      persistence::plugin,
+      popup::plugin,
+      subathon::plugin,
+      urgency::plugin,
+    ))
     .add_systems(FixedUpdate, update_clocks)
-    .add_systems(Update, update_font_size)
     .add_observer(make_clock)
     .add_observer(add_time)
-    .add_observer(add_clock);
+    .add_observer(pause_clock)
+    .add_observer(resume_clock)
+    .add_observer(reset_clock)
+    .add_observer(set_clock)
+    .add_observer(add_clock)
+    .add_observer(run_finished_actions)
+    .add_observer(show_restarted_clock);
+}
+
+fn add_time(trigger: Trigger<AddTime>, mut clocks: TargetedClocks, mut commands: Commands) {
+  for (entity, mut clock, id, child_of) in &mut clocks {
+    if !trigger.target.matches(entity, id, child_of) {
+      continue;
+    }
+
+    let seconds = if trigger.seconds > 0.0 && trigger.multiplied {
+      trigger.seconds * clock.multiplier(MultiplierKind::Contribution)
+    } else {
+      trigger.seconds
+    };
+    let before = clock.time_left();
+    clock.add_seconds(seconds);
+    let seconds = clock.time_left() - before;
+    if seconds != 0.0 {
+      commands.trigger(ClockTimeAdded { entity, seconds });
+    }
+  }
+}
+
+fn pause_clock(trigger: Trigger<PauseClock>, mut clocks: TargetedClocks) {
+  for_each_target(&mut clocks, &trigger.target, Clock::pause);
+}
+
+fn resume_clock(trigger: Trigger<ResumeClock>, mut clocks: TargetedClocks) {
+  for_each_target(&mut clocks, &trigger.target, Clock::resume);
+}
+
+fn reset_clock(trigger: Trigger<ResetClock>, mut clocks: TargetedClocks) {
+  for_each_target(&mut clocks, &trigger.target, |clock| {
+    clock.restart(trigger.seconds)
+  });
+}
+
+fn set_clock(trigger: Trigger<SetClock>, mut clocks: TargetedClocks) {
+  for_each_target(&mut clocks, &trigger.target, |clock| {
+    clock.set_seconds(trigger.seconds)
+  });
 }
 
-fn add_time(trigger: Trigger<AddTime>, mut clocks: Query<&mut Clock>) {
-  for mut clock in &mut clocks {
-    let new_seconds = clock.duration.remaining_secs() + trigger.seconds;
-    clock
-      .duration
-      .set_duration(Duration::from_secs_f32(new_seconds));
-    clock.duration.reset();
+fn for_each_target(
+  clocks: &mut TargetedClocks,
+  target: &ClockTarget,
+  mut f: impl FnMut(&mut Clock),
+) {
+  for (entity, mut clock, id, child_of) in clocks {
+    if target.matches(entity, id, child_of) {
+      f(&mut clock);
+    }
   }
 }
 
-fn update_font_size(
-  mut clocks: Query<(Entity, &ChildOf, &mut TextFont), With<Clock>>,
-  mut transforms: Query<&mut Transform>,
+fn update_clocks(
+  mut clocks: Query<(Entity, &mut Clock, &mut Text2d)>,
+  time: Res<Time>,
   mut commands: Commands,
 ) {
-  for (entity, child_of, mut text_font) in &mut clocks {
-    if let Ok(mut transform) = transforms.get_mut(child_of.parent()) {
-      let new_font_size = text_font.font_size * transform.scale.x;
-      transform.scale = Vec3::splat(1.0);
+  for (entity, mut clock, mut text) in &mut clocks {
+    clock.tick(time.delta());
 
-      commands
-        .entity(entity)
-        .insert(TextFont::from_font_size(new_font_size));
+    let finished = clock.has_run_out();
+    if finished != clock.finished {
+      clock.finished = finished;
+      if finished {
+        commands.trigger(ClockFinished { entity });
+      } else {
+        commands.trigger(ClockRestarted { entity });
+      }
     }
+
+    // Paused clocks blink so it is obvious they aren't counting.
+    let blinked_off =
+      clock.is_paused() && (time.elapsed_secs() / PAUSED_BLINK_SECONDS) as u32 % 2 == 1;
+    text.0 = if blinked_off {
+      String::new()
+    } else {
+      clock.format.format(clock.seconds())
+    };
   }
 }
 
-fn update_clocks(mut clocks: Query<(&mut Clock, &mut Text2d)>, time: Res<Time>) {
-  for (mut clock, mut text) in &mut clocks {
-    clock.duration.tick(time.delta());
+fn run_finished_actions(
+  trigger: Trigger<ClockFinished>,
+  mut clocks: Query<(&mut Clock, Option<&ChildOf>)>,
+  mut commands: Commands,
+) {
+  let entity = trigger.entity;
+  let Ok((mut clock, child_of)) = clocks.get_mut(entity) else {
+    return;
+  };
 
-    let mut seconds_left = clock.duration.remaining_secs();
-    let hr = (seconds_left.floor() as u32) / SECONDS_IN_HOUR;
-    seconds_left -= (hr * SECONDS_IN_HOUR) as f32;
-    let mm = (seconds_left.floor() as u32) / SECONDS_IN_MINUTE;
-    seconds_left -= (mm * SECONDS_IN_MINUTE) as f32;
-    let secs = seconds_left as u32;
+  for action in clock.on_finished.clone() {
+    match action {
+      ClockFinishedAction::Fireworks(seconds) => {
+        commands.trigger(CreateFireworks::new(seconds));
+      }
+      ClockFinishedAction::Hide => {
+        commands
+          .entity(widget_of(entity, child_of))
+          .insert(Visibility::Hidden);
+      }
+      ClockFinishedAction::ChatMessage(message) => {
+        commands.trigger(ClockChatMessage(message));
+      }
+      ClockFinishedAction::Overtime => {
+        clock.mode = ClockMode::Stopwatch;
+        clock.elapsed.reset();
+        clock.overtime = true;
+      }
+    }
+  }
+}
 
-    text.0 = format!(
-      "{}{}:{}{}:{}{}",
-      if hr >= 10 { "" } else { "0" },
-      hr,
-      if mm >= 10 { "" } else { "0" },
-      mm,
-      if secs >= 10 { "" } else { "0" },
-      secs
-    );
+fn show_restarted_clock(
+  trigger: Trigger<ClockRestarted>,
+  clocks: Query<(&Clock, Option<&ChildOf>)>,
+  mut commands: Commands,
+) {
+  let entity = trigger.entity;
+  if let Ok((clock, child_of)) = clocks.get(entity) {
+    if clock.on_finished.contains(&ClockFinishedAction::Hide) {
+      commands
+        .entity(widget_of(entity, child_of))
+        .insert(Visibility::Inherited);
+    }
   }
 }
 
+/// Clocks made with `MakeClock` sit on a widget, which is what gets hidden.
+fn widget_of(entity: Entity, child_of: Option<&ChildOf>) -> Entity {
+  child_of.map_or(entity, ChildOf::parent)
+}
+
+/// `seconds` as a `Duration`, held between zero and `MAX_CLOCK_SECONDS`.
+fn clock_duration(seconds: f32) -> Duration {
+  Duration::try_from_secs_f32(seconds.clamp(0.0, MAX_CLOCK_SECONDS)).unwrap_or_default()
+}
+
+/// `seconds` as a `TimeDelta`, held within `MAX_CLOCK_SECONDS` either way.
+fn clock_time_delta(seconds: f32) -> TimeDelta {
+  let seconds = seconds.clamp(-MAX_CLOCK_SECONDS, MAX_CLOCK_SECONDS);
+  TimeDelta::milliseconds((seconds * 1000.0) as i64)
+}
+
 fn make_clock(
   trigger: Trigger<OnAdd, MakeClock>,
+  make_clock: Query<&MakeClock>,
   mut meshes: ResMut<Assets<Mesh>>,
   mut commands: Commands,
 ) {
-  commands
-    .entity(trigger.target())
-    .insert((Mesh2d(meshes.add(Rectangle::new(80.0, 20.0))),))
-    .with_child(Clock::new(120.0))
-    .remove::<MakeClock>();
+  if let Ok(make_clock) = make_clock.get(trigger.target()) {
+    let mut clock = commands.spawn((make_clock.clock.clone(), ChildOf(trigger.target())));
+    if let Some(id) = &make_clock.id {
+      clock.insert(id.clone());
+    }
+    if let Some(popups) = &make_clock.popups {
+      clock.insert(popups.clone());
+    }
+    if let Some(urgency) = &make_clock.urgency {
+      clock.insert(urgency.clone());
+    }
+
+    commands
+      .entity(trigger.target())
+      .insert((Mesh2d(meshes.add(Rectangle::new(80.0, 20.0))),))
+      .remove::<MakeClock>();
+  }
 }
 
-fn add_clock(trigger: Trigger<OnAdd, Clock>, mut commands: Commands) {
-  commands
-    .entity(trigger.target())
-    .insert(Text2d::new("00:00:00"));
+fn add_clock(trigger: Trigger<OnAdd, Clock>, clocks: Query<&Clock>, mut commands: Commands) {
+  if let Ok(clock) = clocks.get(trigger.target()) {
+    commands.entity(trigger.target()).insert((
+      Text2d::new(clock.format.format(clock.seconds())),
+      CrispText::default(),
+    ));
+  }
 }
 
 impl Clock {
+  /// A countdown starting at `seconds`.
   pub fn new(seconds: f32) -> Clock {
     Clock {
+      mode: ClockMode::Countdown,
+      format: ClockFormat::default(),
       duration: Timer::from_seconds(seconds, TimerMode::Once),
+      elapsed: Stopwatch::new(),
+      on_finished: Vec::new(),
+      finished: false,
+      overtime: false,
+      paused_at: None,
+      real_time: false,
+      synced_at: None,
+      multipliers: Vec::new(),
+    }
+  }
+
+  pub fn stopwatch() -> Clock {
+    Clock {
+      mode: ClockMode::Stopwatch,
+      ..Clock::new(0.0)
+    }
+  }
+
+  /// Counts down to the next time the local wall clock reads `time`, which
+  /// is tomorrow if that time has already passed today.
+  pub fn countdown_to(time: NaiveTime) -> Clock {
+    let now = Local::now();
+    let mut target = now.with_time(time).earliest().unwrap_or(now);
+    if target <= now {
+      target = (now + TimeDelta::days(1))
+        .with_time(time)
+        .earliest()
+        .unwrap_or(target);
+    }
+
+    Clock {
+      mode: ClockMode::CountdownTo(target),
+      ..Clock::new(0.0)
+    }
+  }
+
+  pub fn with_format(mut self, format: ClockFormat) -> Clock {
+    self.format = format;
+    self
+  }
+
+  /// Keeps time by the system clock rather than by adding up frame times, so
+  /// it can't drift over a long stream or fall behind while the app is
+  /// throttled or minimised. Wall clock countdowns always do this.
+  pub fn with_real_time(mut self) -> Clock {
+    self.real_time = true;
+    self
+  }
+
+  /// Schedules a time rate or contribution multiplier, which can also be
+  /// done later with `ScheduleMultiplier`.
+  pub fn with_multiplier(mut self, kind: MultiplierKind, window: MultiplierWindow) -> Clock {
+    self.multipliers.push((kind, window));
+    self
+  }
+
+  /// Adds something to do when the clock runs out, actions run in the order
+  /// they were added.
+  pub fn with_finished_action(mut self, action: ClockFinishedAction) -> Clock {
+    self.on_finished.push(action);
+    self
+  }
+
+  pub fn mode(&self) -> ClockMode {
+    self.mode
+  }
+
+  /// The combined multiplier of every active window of this kind, `1.0`
+  /// when none are. Never more than `MAX_MULTIPLIER`.
+  pub fn multiplier(&self, kind: MultiplierKind) -> f32 {
+    let now = Local::now();
+    self
+      .multipliers
+      .iter()
+      .filter(|(window_kind, window)| *window_kind == kind && window.is_active(now))
+      .map(|(_, window)| window.multiplier)
+      .product::<f32>()
+      .min(multiplier::MAX_MULTIPLIER)
+  }
+
+  pub fn is_paused(&self) -> bool {
+    self.paused_at.is_some()
+  }
+
+  pub fn pause(&mut self) {
+    if self.paused_at.is_none() {
+      // Counts the time since the last tick before stopping.
+      if self.real_time {
+        self.tick(Duration::ZERO);
+      }
+      self.paused_at = Some(Local::now());
+    }
+  }
+
+  pub fn resume(&mut self) {
+    let Some(paused_at) = self.paused_at.take() else {
+      return;
+    };
+    // Skips the time since the last tick, which was spent paused.
+    self.catch_up();
+    if let ClockMode::CountdownTo(target) = &mut self.mode {
+      *target += Local::now() - paused_at;
+    }
+  }
+
+  /// The time to show: what is left for countdowns, or how long it has been
+  /// running for stopwatches. Real time clocks include the time since they
+  /// last ticked, so they are right whenever they are read.
+  pub fn seconds(&self) -> f32 {
+    let unsynced = self.unsynced().as_secs_f32();
+    match self.mode {
+      ClockMode::Countdown => {
+        let rate = self.multiplier(MultiplierKind::TimeRate).max(0.0);
+        (self.duration.remaining_secs() - unsynced * rate).max(0.0)
+      }
+      ClockMode::Stopwatch => self.elapsed.elapsed_secs() + unsynced,
+      ClockMode::CountdownTo(target) => {
+        (target - self.now()).num_milliseconds().max(0) as f32 / 1000.0
+      }
+    }
+  }
+
+  /// What is left of a countdown, zero for stopwatches and overtime.
+  pub fn time_left(&self) -> f32 {
+    match self.mode {
+      ClockMode::Countdown | ClockMode::CountdownTo(_) => self.seconds(),
+      ClockMode::Stopwatch => 0.0,
+    }
+  }
+
+  /// Whether the countdown has reached zero, or is counting overtime since
+  /// it did.
+  pub fn has_run_out(&self) -> bool {
+    match self.mode {
+      ClockMode::Countdown | ClockMode::CountdownTo(_) => self.seconds() <= 0.0,
+      ClockMode::Stopwatch => self.overtime,
+    }
+  }
+
+  /// Starts counting down again from `seconds`, whatever mode the clock was
+  /// in. Infinite or NaN `seconds` are ignored.
+  pub fn restart(&mut self, seconds: f32) {
+    if !seconds.is_finite() {
+      return;
+    }
+    self.catch_up();
+    self.mode = ClockMode::Countdown;
+    self.overtime = false;
+    self.elapsed.reset();
+    self.duration.set_duration(clock_duration(seconds));
+    self.duration.reset();
+  }
+
+  /// Makes a countdown longer, or shorter for negative `seconds`, or moves a
+  /// wall clock target. Countdowns never go below zero. Time added during
+  /// overtime starts a new countdown. Other stopwatches only ever show real
+  /// time so are left alone. Infinite or NaN `seconds` are ignored.
+  pub fn add_seconds(&mut self, seconds: f32) {
+    if !seconds.is_finite() {
+      return;
+    }
+    if self.overtime {
+      if seconds > 0.0 {
+        self.restart(seconds);
+      }
+      return;
+    }
+
+    let now = self.now();
+    match &mut self.mode {
+      ClockMode::Countdown => {
+        let new_seconds = self.duration.remaining_secs() + seconds;
+        self.duration.set_duration(clock_duration(new_seconds));
+        self.duration.reset();
+      }
+      ClockMode::Stopwatch => {}
+      ClockMode::CountdownTo(target) => {
+        let latest = now + clock_time_delta(MAX_CLOCK_SECONDS);
+        *target = target
+          .checked_add_signed(clock_time_delta(seconds))
+          .unwrap_or(*target)
+          .clamp(now, latest);
+      }
     }
   }
+
+  /// Makes the clock show `seconds`, as what is left for countdowns or how
+  /// long it has been running for stopwatches. Setting a clock in overtime
+  /// starts a new countdown. Infinite or NaN `seconds` are ignored.
+  pub fn set_seconds(&mut self, seconds: f32) {
+    if !seconds.is_finite() {
+      return;
+    }
+    if self.overtime {
+      self.restart(seconds);
+      return;
+    }
+    self.catch_up();
+
+    let now = self.now();
+    match &mut self.mode {
+      ClockMode::Countdown => {
+        self.duration.set_duration(clock_duration(seconds));
+        self.duration.reset();
+      }
+      ClockMode::Stopwatch => {
+        self.elapsed.set_elapsed(clock_duration(seconds));
+      }
+      ClockMode::CountdownTo(target) => {
+        *target = now + clock_time_delta(seconds.max(0.0));
+      }
+    }
+  }
+
+  /// The wall clock time the clock is at, which stands still while paused.
+  fn now(&self) -> DateTime<Local> {
+    self.paused_at.unwrap_or_else(Local::now)
+  }
+
+  /// Moves the clock on by `delta`, or by however long it has really been
+  /// for real time clocks.
+  fn tick(&mut self, delta: Duration) {
+    let delta = if self.real_time {
+      self.catch_up()
+    } else {
+      delta
+    };
+    let now = Local::now();
+    self.multipliers.retain(|(_, window)| window.end() > now);
+    if self.is_paused() {
+      return;
+    }
+
+    let rate = self.multiplier(MultiplierKind::TimeRate).max(0.0);
+    match &mut self.mode {
+      ClockMode::Countdown => {
+        self.duration.tick(delta.mul_f32(rate));
+      }
+      ClockMode::Stopwatch => {
+        self.elapsed.tick(delta);
+      }
+      // Wall clock countdowns run at a different speed by moving the moment
+      // they count down to.
+      ClockMode::CountdownTo(target) => {
+        let shift = delta.as_secs_f32() * (1.0 - rate);
+        let shift = TimeDelta::microseconds((shift * 1_000_000.0) as i64);
+        *target = target.checked_add_signed(shift).unwrap_or(*target);
+      }
+    }
+  }
+
+  /// How long a running real time clock has gone without catching up.
+  fn unsynced(&self) -> Duration {
+    match self.synced_at {
+      Some(synced_at) if self.real_time && !self.is_paused() => synced_at.elapsed(),
+      _ => Duration::ZERO,
+    }
+  }
+
+  /// How long it has been since a real time clock last caught up.
+  fn catch_up(&mut self) -> Duration {
+    if !self.real_time {
+      return Duration::ZERO;
+    }
+
+    let now = Instant::now();
+    let missed = self
+      .synced_at
+      .map_or(Duration::ZERO, |synced_at| now.duration_since(synced_at));
+    self.synced_at = Some(now);
+    missed
+  }
 }
 
 impl AddTime {
+  /// Adds time to every clock.
   pub fn new(seconds: f32) -> AddTime {
-    AddTime { seconds }
+    AddTime {
+      seconds,
+      target: ClockTarget::All,
+      multiplied: true,
+    }
+  }
+
+  /// Takes time off every clock, stopping at zero.
+  pub fn subtract(seconds: f32) -> AddTime {
+    AddTime::new(-seconds.abs())
+  }
+
+  pub fn for_clock<S: Into<String>>(id: S, seconds: f32) -> AddTime {
+    AddTime::new(seconds).with_target(ClockTarget::Id(ClockId::new(id)))
+  }
+
+  pub fn with_target(mut self, target: ClockTarget) -> AddTime {
+    self.target = target;
+    self
+  }
+
+  /// Adds exactly `seconds`, for time that has already been through the
+  /// clock's contribution multiplier.
+  pub fn without_multipliers(mut self) -> AddTime {
+    self.multiplied = false;
+    self
+  }
+}
+
+impl PauseClock {
+  /// Pauses every clock.
+  pub fn new() -> PauseClock {
+    PauseClock::default()
+  }
+
+  pub fn for_clock<S: Into<String>>(id: S) -> PauseClock {
+    PauseClock::new().with_target(ClockTarget::Id(ClockId::new(id)))
+  }
+
+  pub fn with_target(mut self, target: ClockTarget) -> PauseClock {
+    self.target = target;
+    self
+  }
+}
+
+impl ResumeClock {
+  /// Resumes every clock.
+  pub fn new() -> ResumeClock {
+    ResumeClock::default()
+  }
+
+  pub fn for_clock<S: Into<String>>(id: S) -> ResumeClock {
+    ResumeClock::new().with_target(ClockTarget::Id(ClockId::new(id)))
+  }
+
+  pub fn with_target(mut self, target: ClockTarget) -> ResumeClock {
+    self.target = target;
+    self
+  }
+}
+
+impl ResetClock {
+  /// Resets every clock.
+  pub fn new(seconds: f32) -> ResetClock {
+    ResetClock {
+      seconds,
+      target: ClockTarget::All,
+    }
+  }
+
+  pub fn for_clock<S: Into<String>>(id: S, seconds: f32) -> ResetClock {
+    ResetClock::new(seconds).with_target(ClockTarget::Id(ClockId::new(id)))
+  }
+
+  pub fn with_target(mut self, target: ClockTarget) -> ResetClock {
+    self.target = target;
+    self
+  }
+}
+
+impl SetClock {
+  /// Sets every clock.
+  pub fn new(seconds: f32) -> SetClock {
+    SetClock {
+      seconds,
+      target: ClockTarget::All,
+    }
+  }
+
+  pub fn for_clock<S: Into<String>>(id: S, seconds: f32) -> SetClock {
+    SetClock::new(seconds).with_target(ClockTarget::Id(ClockId::new(id)))
+  }
+
+  pub fn with_target(mut self, target: ClockTarget) -> SetClock {
+    self.target = target;
+    self
+  }
+}
+
+impl ClockTarget {
+  pub fn matches(&self, entity: Entity, id: Option<&ClockId>, child_of: Option<&ChildOf>) -> bool {
+    match self {
+      ClockTarget::All => true,
+      ClockTarget::Entity(target) => {
+        *target == entity || child_of.is_some_and(|child_of| child_of.parent() == *target)
+      }
+      ClockTarget::Id(target) => id == Some(target),
+    }
+  }
+}
+
+impl ClockId {
+  pub fn new<S: Into<String>>(id: S) -> ClockId {
+    ClockId(id.into())
+  }
+}
+
+impl MakeClock {
+  pub fn new(clock: Clock) -> MakeClock {
+    MakeClock {
+      clock,
+      id: None,
+      popups: None,
+      urgency: None,
+    }
+  }
+
+  pub fn with_id<S: Into<String>>(mut self, id: S) -> MakeClock {
+    self.id = Some(ClockId::new(id));
+    self
+  }
+
+  pub fn with_popups(mut self, popups: TimePopups) -> MakeClock {
+    self.popups = Some(popups);
+    self
+  }
+
+  pub fn with_urgency(mut self, urgency: ClockUrgency) -> MakeClock {
+    self.urgency = Some(urgency);
+    self
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[derive(Resource, Default)]
+  struct Finished(u32);
+
+  #[test]
+  fn subtracting_past_zero_clamps() {
+    let mut clock = Clock::new(10.0);
+    clock.add_seconds(-30.0);
+    assert_eq!(clock.time_left(), 0.0);
+    assert!(clock.has_run_out());
+
+    // Adding time afterwards counts from zero, not from minus twenty.
+    clock.add_seconds(5.0);
+    assert_eq!(clock.time_left(), 5.0);
+  }
+
+  #[test]
+  fn subtracting_past_zero_finishes_next_tick() {
+    let mut app = App::new();
+    app
+      .add_plugins(MinimalPlugins)
+      .init_resource::<Finished>()
+      .add_systems(Update, update_clocks)
+      .add_observer(|_: Trigger<ClockFinished>, mut finished: ResMut<Finished>| finished.0 += 1);
+    let entity = app
+      .world_mut()
+      .spawn((Clock::new(10.0), Text2d::default()))
+      .id();
+    app.update();
+    assert_eq!(app.world().resource::<Finished>().0, 0);
+
+    app
+      .world_mut()
+      .get_mut::<Clock>(entity)
+      .unwrap()
+      .add_seconds(-30.0);
+    app.update();
+    assert_eq!(app.world().resource::<Finished>().0, 1);
+    assert_eq!(app.world().get::<Text2d>(entity).unwrap().0, "00:00:00");
+
+    app.update();
+    assert_eq!(app.world().resource::<Finished>().0, 1);
+  }
+
+  #[test]
+  fn countdown_to_clamps_at_now() {
+    let mut clock = Clock {
+      mode: ClockMode::CountdownTo(Local::now() + TimeDelta::seconds(10)),
+      ..Clock::new(0.0)
+    };
+    clock.add_seconds(-60.0);
+    assert_eq!(clock.time_left(), 0.0);
+    assert!(clock.has_run_out());
+
+    // The target was moved to now rather than fifty seconds ago.
+    clock.add_seconds(5.0);
+    let left = clock.time_left();
+    assert!(left > 4.5 && left <= 5.0, "{} left", left);
+  }
+
+  #[test]
+  fn subtracting_during_overtime_is_ignored() {
+    let mut clock = Clock {
+      mode: ClockMode::Stopwatch,
+      overtime: true,
+      ..Clock::new(0.0)
+    };
+    clock.add_seconds(-30.0);
+    assert_eq!(clock.mode(), ClockMode::Stopwatch);
+    assert!(clock.has_run_out());
+  }
+
+  fn every_mode() -> [Clock; 4] {
+    [
+      Clock::new(10.0),
+      Clock::stopwatch(),
+      Clock::countdown_to(NaiveTime::from_hms_opt(12, 0, 0).unwrap()),
+      Clock {
+        mode: ClockMode::Stopwatch,
+        overtime: true,
+        ..Clock::new(0.0)
+      },
+    ]
+  }
+
+  #[test]
+  fn extreme_seconds_dont_panic() {
+    for seconds in [
+      f32::MAX,
+      f32::MIN,
+      f32::INFINITY,
+      f32::NEG_INFINITY,
+      f32::NAN,
+    ] {
+      for mut clock in every_mode() {
+        clock.add_seconds(seconds);
+        clock.add_seconds(seconds);
+        clock.set_seconds(seconds);
+        clock.restart(seconds);
+        assert!(clock.seconds() <= MAX_CLOCK_SECONDS);
+      }
+    }
+  }
+
+  #[test]
+  fn non_finite_seconds_are_ignored() {
+    for seconds in [f32::INFINITY, f32::NEG_INFINITY, f32::NAN] {
+      let mut clock = Clock::new(10.0);
+      clock.add_seconds(seconds);
+      clock.set_seconds(seconds);
+      clock.restart(seconds);
+      assert_eq!(clock.time_left(), 10.0);
+    }
+  }
+
+  #[test]
+  fn huge_seconds_are_clamped() {
+    let mut clock = Clock::new(10.0);
+    clock.add_seconds(f32::MAX);
+    assert_eq!(clock.time_left(), MAX_CLOCK_SECONDS);
+
+    let mut clock = Clock::countdown_to(NaiveTime::from_hms_opt(12, 0, 0).unwrap());
+    clock.add_seconds(f32::MAX);
+    clock.add_seconds(f32::MAX);
+    assert!((clock.time_left() - MAX_CLOCK_SECONDS).abs() < 1.0);
+
+    let mut clock = Clock::stopwatch();
+    clock.set_seconds(f32::MAX);
+    assert_eq!(clock.seconds(), MAX_CLOCK_SECONDS);
+  }
+
+  /// How long real time clocks are left alone for, long enough that a tick
+  /// missing it would be obvious.
+  const STALL: Duration = Duration::from_millis(50);
+
+  /// Asserts a real time clock counted at least `min` and at most `max`.
+  fn assert_counted(counted: f32, min: Duration, max: Duration) {
+    assert!(
+      counted >= min.as_secs_f32() - 1e-4 && counted <= max.as_secs_f32() + 1e-4,
+      "counted {} outside {:?}..{:?}",
+      counted,
+      min,
+      max
+    );
+  }
+
+  #[test]
+  fn real_time_catches_up_after_a_stall() {
+    let mut countdown = Clock::new(100.0).with_real_time();
+    let mut stopwatch = Clock::stopwatch().with_real_time();
+    let before = Instant::now();
+    countdown.tick(Duration::ZERO);
+    stopwatch.tick(Duration::ZERO);
+    let synced = Instant::now();
+
+    std::thread::sleep(STALL);
+    // The frame claims hardly any time passed.
+    let stalled_for = synced.elapsed();
+    countdown.tick(Duration::from_millis(1));
+    stopwatch.tick(Duration::from_millis(1));
+    let max = before.elapsed();
+
+    assert_counted(100.0 - countdown.seconds(), stalled_for, max);
+    assert_counted(stopwatch.seconds(), stalled_for, max);
+  }
+
+  #[test]
+  fn real_time_shows_time_since_the_last_tick() {
+    let mut clock = Clock::new(100.0).with_real_time();
+    let before = Instant::now();
+    clock.tick(Duration::ZERO);
+    let synced = Instant::now();
+
+    std::thread::sleep(STALL);
+    let min = synced.elapsed();
+    let counted = 100.0 - clock.seconds();
+    assert_counted(counted, min, before.elapsed());
+  }
+
+  #[test]
+  fn real_time_pause_and_resume_skip_paused_time() {
+    for mut clock in [
+      Clock::new(100.0).with_real_time(),
+      Clock::stopwatch().with_real_time(),
+    ] {
+      let shown = |clock: &Clock| match clock.mode() {
+        ClockMode::Stopwatch => clock.seconds(),
+        _ => 100.0 - clock.seconds(),
+      };
+      let before = Instant::now();
+      clock.tick(Duration::ZERO);
+      clock.pause();
+      let paused = Instant::now();
+
+      std::thread::sleep(STALL);
+      assert_counted(shown(&clock), Duration::ZERO, paused - before);
+
+      let paused_for = paused.elapsed();
+      clock.resume();
+      clock.tick(Duration::ZERO);
+      assert_counted(shown(&clock), Duration::ZERO, before.elapsed() - paused_for);
+    }
   }
 }
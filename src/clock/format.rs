@@ -0,0 +1,242 @@
+use std::fmt::Write;
+
+const SECONDS_IN_DAY: u64 = 86400;
+const SECONDS_IN_HOUR: u64 = 3600;
+const SECONDS_IN_MINUTE: u64 = 60;
+
+/// How a clock turns its time into text.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClockFormat {
+  pub units: ClockUnits,
+  pub fraction: ClockFraction,
+  /// Leaves off units at the front that are zero, so `00:05:30` reads
+  /// `5:30`. Seconds are always shown.
+  pub hide_leading_zeros: bool,
+  pub prefix: String,
+  pub suffix: String,
+}
+
+/// The largest unit shown. It is never wrapped, so 100 hours shows as
+/// `100:00:00` rather than rolling over.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ClockUnits {
+  /// `MM:SS`
+  MinutesSeconds,
+  /// `HH:MM:SS`
+  #[default]
+  HoursMinutesSeconds,
+  /// `Dd HH:MM:SS`, for subathons that run for days.
+  DaysHoursMinutesSeconds,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ClockFraction {
+  #[default]
+  None,
+  Tenths,
+  Milliseconds,
+}
+
+impl ClockFormat {
+  pub fn new() -> ClockFormat {
+    ClockFormat::default()
+  }
+
+  pub fn with_units(mut self, units: ClockUnits) -> ClockFormat {
+    self.units = units;
+    self
+  }
+
+  pub fn with_fraction(mut self, fraction: ClockFraction) -> ClockFormat {
+    self.fraction = fraction;
+    self
+  }
+
+  pub fn with_hidden_leading_zeros(mut self, hide: bool) -> ClockFormat {
+    self.hide_leading_zeros = hide;
+    self
+  }
+
+  /// Text shown before the time, such as `"Ends in "`.
+  pub fn with_prefix<S: Into<String>>(mut self, prefix: S) -> ClockFormat {
+    self.prefix = prefix.into();
+    self
+  }
+
+  pub fn with_suffix<S: Into<String>>(mut self, suffix: S) -> ClockFormat {
+    self.suffix = suffix.into();
+    self
+  }
+
+  /// Formats `seconds`, rounding down so a countdown never shows a second
+  /// that has already gone. Negative times show as zero.
+  pub fn format(&self, seconds: f32) -> String {
+    let total_millis = (f64::from(seconds.max(0.0)) * 1000.0).floor() as u64;
+    let total_seconds = total_millis / 1000;
+    let millis = total_millis % 1000;
+
+    let minutes = total_seconds % SECONDS_IN_HOUR / SECONDS_IN_MINUTE;
+    let secs = total_seconds % SECONDS_IN_MINUTE;
+    // Each unit along with the separator that follows it.
+    let units = match self.units {
+      ClockUnits::MinutesSeconds => vec![(total_seconds / SECONDS_IN_MINUTE, ":"), (secs, "")],
+      ClockUnits::HoursMinutesSeconds => vec![
+        (total_seconds / SECONDS_IN_HOUR, ":"),
+        (minutes, ":"),
+        (secs, ""),
+      ],
+      ClockUnits::DaysHoursMinutesSeconds => vec![
+        (total_seconds / SECONDS_IN_DAY, "d "),
+        (total_seconds % SECONDS_IN_DAY / SECONDS_IN_HOUR, ":"),
+        (minutes, ":"),
+        (secs, ""),
+      ],
+    };
+
+    let first_shown = if self.hide_leading_zeros {
+      units
+        .iter()
+        .position(|(value, _)| *value != 0)
+        .unwrap_or(units.len() - 1)
+    } else {
+      0
+    };
+    let days_shown = self.units == ClockUnits::DaysHoursMinutesSeconds && first_shown == 0;
+
+    let mut text = self.prefix.clone();
+    for (i, (value, separator)) in units.iter().enumerate().skip(first_shown) {
+      if i == first_shown && (self.hide_leading_zeros || days_shown) {
+        let _ = write!(text, "{}{}", value, separator);
+      } else {
+        let _ = write!(text, "{:02}{}", value, separator);
+      }
+    }
+
+    let _ = match self.fraction {
+      ClockFraction::None => Ok(()),
+      ClockFraction::Tenths => write!(text, ".{}", millis / 100),
+      ClockFraction::Milliseconds => write!(text, ".{:03}", millis),
+    };
+
+    text.push_str(&self.suffix);
+    text
+  }
+}
+
+impl Default for ClockFormat {
+  fn default() -> Self {
+    ClockFormat {
+      units: ClockUnits::HoursMinutesSeconds,
+      fraction: ClockFraction::None,
+      hide_leading_zeros: false,
+      prefix: String::new(),
+      suffix: String::new(),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn format(units: ClockUnits, seconds: f32) -> String {
+    ClockFormat::new().with_units(units).format(seconds)
+  }
+
+  #[test]
+  fn minutes_seconds() {
+    assert_eq!(format(ClockUnits::MinutesSeconds, 65.0), "01:05");
+    // Hours are folded into the minutes rather than dropped.
+    assert_eq!(format(ClockUnits::MinutesSeconds, 3725.0), "62:05");
+  }
+
+  #[test]
+  fn hours_minutes_seconds() {
+    assert_eq!(format(ClockUnits::HoursMinutesSeconds, 3725.0), "01:02:05");
+    assert_eq!(format(ClockUnits::HoursMinutesSeconds, 59.9), "00:00:59");
+  }
+
+  #[test]
+  fn days_hours_minutes_seconds() {
+    assert_eq!(
+      format(ClockUnits::DaysHoursMinutesSeconds, 90061.0),
+      "1d 01:01:01"
+    );
+    assert_eq!(
+      format(ClockUnits::DaysHoursMinutesSeconds, 3725.0),
+      "0d 01:02:05"
+    );
+  }
+
+  #[test]
+  fn past_99_hours() {
+    assert_eq!(
+      format(ClockUnits::HoursMinutesSeconds, 360_000.0),
+      "100:00:00"
+    );
+    assert_eq!(
+      format(ClockUnits::HoursMinutesSeconds, 360_061.0),
+      "100:01:01"
+    );
+    assert_eq!(
+      format(ClockUnits::HoursMinutesSeconds, 3_600_000.0),
+      "1000:00:00"
+    );
+    assert_eq!(format(ClockUnits::MinutesSeconds, 360_000.0), "6000:00");
+    assert_eq!(
+      format(ClockUnits::DaysHoursMinutesSeconds, 8_640_000.0),
+      "100d 00:00:00"
+    );
+  }
+
+  #[test]
+  fn fractions() {
+    let tenths = ClockFormat::new().with_fraction(ClockFraction::Tenths);
+    assert_eq!(tenths.format(5.25), "00:00:05.2");
+    assert_eq!(tenths.format(5.99), "00:00:05.9");
+
+    let millis = ClockFormat::new().with_fraction(ClockFraction::Milliseconds);
+    assert_eq!(millis.format(5.125), "00:00:05.125");
+    assert_eq!(millis.format(61.0), "00:01:01.000");
+  }
+
+  #[test]
+  fn hidden_leading_zeros() {
+    let hidden = |units| {
+      ClockFormat::new()
+        .with_units(units)
+        .with_hidden_leading_zeros(true)
+    };
+    assert_eq!(
+      hidden(ClockUnits::HoursMinutesSeconds).format(330.0),
+      "5:30"
+    );
+    assert_eq!(
+      hidden(ClockUnits::HoursMinutesSeconds).format(3725.0),
+      "1:02:05"
+    );
+    assert_eq!(hidden(ClockUnits::HoursMinutesSeconds).format(5.0), "5");
+    assert_eq!(hidden(ClockUnits::HoursMinutesSeconds).format(0.0), "0");
+    assert_eq!(
+      hidden(ClockUnits::DaysHoursMinutesSeconds).format(3725.0),
+      "1:02:05"
+    );
+    assert_eq!(
+      hidden(ClockUnits::HoursMinutesSeconds).format(360_000.0),
+      "100:00:00"
+    );
+  }
+
+  #[test]
+  fn prefix_and_suffix() {
+    let format = ClockFormat::new().with_prefix("Ends in ").with_suffix("!");
+    assert_eq!(format.format(65.0), "Ends in 00:01:05!");
+  }
+
+  #[test]
+  fn negative_is_zero() {
+    assert_eq!(format(ClockUnits::HoursMinutesSeconds, -5.0), "00:00:00");
+    let tenths = ClockFormat::new().with_fraction(ClockFraction::Tenths);
+    assert_eq!(tenths.format(-0.5), "00:00:00.0");
+  }
+}
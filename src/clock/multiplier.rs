@@ -0,0 +1,301 @@
+use bevy::{prelude::*, sprite::Anchor, text::TextLayoutInfo};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use super::{
+  clock_time_delta, for_each_target, Clock, ClockId, ClockTarget, TargetedClocks, MAX_CLOCK_SECONDS,
+};
+
+/// Badges are this much smaller than the clock's own text.
+const BADGE_FONT_SCALE: f32 = 0.6;
+/// Space between the clock's text and its badge.
+const BADGE_GAP: f32 = 8.0;
+/// The most a multiplier, or several combined, can be.
+pub(super) const MAX_MULTIPLIER: f32 = 1000.0;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MultiplierKind {
+  /// How fast countdowns run, `0.5` for half speed. Stopwatches always show
+  /// real time.
+  TimeRate,
+  /// How much time added with `AddTime` counts for, `2.0` for double. Time
+  /// taken off is never multiplied, and subathon support is multiplied
+  /// before its max and cap.
+  Contribution,
+}
+
+/// A stretch of time a multiplier applies for, such as a happy hour. When
+/// windows overlap their multipliers are combined.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct MultiplierWindow {
+  pub start: DateTime<Local>,
+  pub seconds: f32,
+  pub multiplier: f32,
+}
+
+/// Adds a multiplier window to clocks.
+#[derive(Event)]
+pub struct ScheduleMultiplier {
+  kind: MultiplierKind,
+  window: MultiplierWindow,
+  target: ClockTarget,
+}
+
+/// Shows a clock's active multipliers, such as "x2 time", next to it.
+#[derive(Component)]
+struct MultiplierBadge;
+
+type Badges<'w, 's> = Query<
+  'w,
+  's,
+  (
+    &'static mut Text2d,
+    &'static mut TextFont,
+    &'static mut Transform,
+  ),
+  (With<MultiplierBadge>, Without<Clock>),
+>;
+
+pub(super) fn plugin(app: &mut App) {
+  app
+    .add_systems(Update, show_multiplier_badges)
+    .add_observer(schedule_multiplier);
+}
+
+fn schedule_multiplier(trigger: Trigger<ScheduleMultiplier>, mut clocks: TargetedClocks) {
+  for_each_target(&mut clocks, &trigger.target, |clock| {
+    clock.multipliers.push((trigger.kind, trigger.window))
+  });
+}
+
+/// Badges are spawned while a clock has a multiplier active and despawned
+/// once it has none.
+fn show_multiplier_badges(
+  clocks: Query<(
+    Entity,
+    &Clock,
+    &TextFont,
+    &TextLayoutInfo,
+    Option<&Children>,
+  )>,
+  mut badges: Badges,
+  mut commands: Commands,
+) {
+  for (entity, clock, text_font, layout, children) in &clocks {
+    let text = badge_text(clock);
+    let font_size = text_font.font_size * BADGE_FONT_SCALE;
+    let translation = Vec3::new(layout.size.x * 0.5 + BADGE_GAP, 0.0, 1.0);
+    let badge = children
+      .into_iter()
+      .flatten()
+      .find(|child| badges.contains(**child))
+      .copied();
+
+    match (badge, text) {
+      (Some(badge), Some(text)) => {
+        let Ok((mut badge_text, mut badge_font, mut transform)) = badges.get_mut(badge) else {
+          continue;
+        };
+        if badge_text.0 != text {
+          badge_text.0 = text;
+        }
+        // Follows the clock's font, which grows as the clock is zoomed.
+        if badge_font.font_size != font_size {
+          badge_font.font_size = font_size;
+        }
+        if transform.translation != translation {
+          transform.translation = translation;
+        }
+      }
+      (Some(badge), None) => {
+        commands.entity(badge).despawn();
+      }
+      (None, Some(text)) => {
+        commands.spawn((
+          MultiplierBadge,
+          Text2d::new(text),
+          text_font.clone().with_font_size(font_size),
+          Anchor::CenterLeft,
+          Transform::from_translation(translation),
+          ChildOf(entity),
+        ));
+      }
+      (None, None) => {}
+    }
+  }
+}
+
+fn badge_text(clock: &Clock) -> Option<String> {
+  let parts = [
+    (MultiplierKind::TimeRate, "speed"),
+    (MultiplierKind::Contribution, "time"),
+  ]
+  .into_iter()
+  .map(|(kind, label)| (format_multiplier(clock.multiplier(kind)), label))
+  .filter(|(multiplier, _)| multiplier != "1")
+  .map(|(multiplier, label)| format!("x{} {}", multiplier, label))
+  .collect::<Vec<_>>();
+
+  (!parts.is_empty()).then(|| parts.join(" "))
+}
+
+/// Rounded to two decimal places without trailing zeros, so combined
+/// windows show `2.2` rather than `2.2000003`.
+fn format_multiplier(multiplier: f32) -> String {
+  let text = format!("{:.2}", multiplier);
+  text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+impl MultiplierWindow {
+  /// Windows last at most `MAX_CLOCK_SECONDS`. A multiplier that is negative
+  /// or not a number counts as `1.0`, and is at most `MAX_MULTIPLIER`.
+  pub fn new(start: DateTime<Local>, seconds: f32, multiplier: f32) -> MultiplierWindow {
+    let seconds = if seconds.is_nan() {
+      0.0
+    } else {
+      seconds.clamp(0.0, MAX_CLOCK_SECONDS)
+    };
+    let multiplier = if multiplier >= 0.0 {
+      multiplier.min(MAX_MULTIPLIER)
+    } else {
+      1.0
+    };
+    MultiplierWindow {
+      start,
+      seconds,
+      multiplier,
+    }
+  }
+
+  /// A window starting straight away.
+  pub fn from_now(seconds: f32, multiplier: f32) -> MultiplierWindow {
+    MultiplierWindow::new(Local::now(), seconds, multiplier)
+  }
+
+  pub fn end(&self) -> DateTime<Local> {
+    self
+      .start
+      .checked_add_signed(clock_time_delta(self.seconds))
+      .unwrap_or(self.start)
+  }
+
+  pub fn is_active(&self, now: DateTime<Local>) -> bool {
+    self.start <= now && now < self.end()
+  }
+}
+
+impl ScheduleMultiplier {
+  /// Schedules the window on every clock.
+  pub fn new(kind: MultiplierKind, window: MultiplierWindow) -> ScheduleMultiplier {
+    ScheduleMultiplier {
+      kind,
+      window,
+      target: ClockTarget::All,
+    }
+  }
+
+  pub fn for_clock<S: Into<String>>(
+    id: S,
+    kind: MultiplierKind,
+    window: MultiplierWindow,
+  ) -> ScheduleMultiplier {
+    ScheduleMultiplier::new(kind, window).with_target(ClockTarget::Id(ClockId::new(id)))
+  }
+
+  pub fn with_target(mut self, target: ClockTarget) -> ScheduleMultiplier {
+    self.target = target;
+    self
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use chrono::TimeDelta;
+
+  use super::*;
+  use crate::clock::ClockMode;
+
+  fn half_speed() -> MultiplierWindow {
+    MultiplierWindow::from_now(3600.0, 0.5)
+  }
+
+  fn assert_near(seconds: f32, expected: f32) {
+    assert!(
+      (seconds - expected).abs() < 0.1,
+      "{} is not {}",
+      seconds,
+      expected
+    );
+  }
+
+  #[test]
+  fn time_rate_slows_countdowns() {
+    let mut clock = Clock::new(100.0).with_multiplier(MultiplierKind::TimeRate, half_speed());
+    clock.tick(Duration::from_secs(10));
+    assert_near(clock.seconds(), 95.0);
+  }
+
+  #[test]
+  fn time_rate_slows_wall_clock_countdowns() {
+    let target = Local::now() + TimeDelta::seconds(100);
+    let mut clock = Clock {
+      mode: ClockMode::CountdownTo(target),
+      ..Clock::new(0.0)
+    }
+    .with_multiplier(MultiplierKind::TimeRate, half_speed());
+
+    // Half of the ten seconds the frame claims passed is given back.
+    clock.tick(Duration::from_secs(10));
+    assert_near(clock.seconds(), 105.0);
+  }
+
+  #[test]
+  fn badges_round_combined_multipliers() {
+    let clock = Clock::new(100.0)
+      .with_multiplier(
+        MultiplierKind::Contribution,
+        MultiplierWindow::from_now(3600.0, 2.0),
+      )
+      .with_multiplier(
+        MultiplierKind::Contribution,
+        MultiplierWindow::from_now(3600.0, 1.1),
+      )
+      .with_multiplier(MultiplierKind::TimeRate, half_speed());
+    assert_eq!(badge_text(&clock).unwrap(), "x0.5 speed x2.2 time");
+    assert_eq!(badge_text(&Clock::new(100.0)), None);
+    assert_eq!(format_multiplier(1.0001), "1");
+    assert_eq!(format_multiplier(3.0), "3");
+  }
+
+  #[test]
+  fn windows_are_validated() {
+    let now = Local::now();
+    for seconds in [f32::MAX, f32::INFINITY, f32::NAN, -5.0] {
+      let window = MultiplierWindow::new(now, seconds, 2.0);
+      assert!(window.seconds >= 0.0 && window.seconds <= MAX_CLOCK_SECONDS);
+      assert!(window.end() >= now);
+    }
+    for multiplier in [f32::NAN, -2.0] {
+      assert_eq!(MultiplierWindow::new(now, 60.0, multiplier).multiplier, 1.0);
+    }
+    assert_eq!(
+      MultiplierWindow::new(now, 60.0, f32::INFINITY).multiplier,
+      MAX_MULTIPLIER
+    );
+
+    // Windows made without `new` still can't overflow.
+    let window = MultiplierWindow {
+      start: now,
+      seconds: f32::INFINITY,
+      multiplier: f32::MAX,
+    };
+    window.end();
+    let mut clock = Clock::new(100.0)
+      .with_multiplier(MultiplierKind::TimeRate, window)
+      .with_multiplier(MultiplierKind::TimeRate, window);
+    clock.tick(Duration::from_secs(1));
+    assert_eq!(clock.seconds(), 0.0);
+  }
+}
@@ -0,0 +1,385 @@
+use std::{
+  collections::BTreeMap,
+  path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use chrono::{DateTime, Local, TimeDelta};
+use serde::{Deserialize, Serialize};
+
+use super::{
+  subathon::SubathonLog, AddTime, Clock, ClockFinished, ClockId, ClockMode, ClockRestarted,
+  MultiplierKind, MultiplierWindow, PauseClock, ResetClock, ResumeClock, ScheduleMultiplier,
+  SetClock,
+};
+use crate::draggable_interface::persistence::{load_ron, save_ron};
+
+const DEFAULT_CLOCKS_PATH: &str = "clocks.ron";
+/// Clocks are saved this often even when nothing has changed them, so a
+/// crash loses at most this much time.
+const SAVE_INTERVAL_SECONDS: f32 = 5.0;
+
+/// Saved state of every clock with a `ClockId`, keyed by that id, so a
+/// subathon carries on where it was after a crash or restart. How much time
+/// support has added is saved too, so the subathon's cap still holds.
+#[derive(Resource)]
+pub struct ClockStore {
+  path: PathBuf,
+  saved: SavedClocks,
+  downtime: ClockDowntime,
+  save_timer: Timer,
+  dirty: bool,
+}
+
+/// What happens to the time that passed while the app wasn't running.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ClockDowntime {
+  /// Clocks carry on as if the app had been running, so a countdown may
+  /// have finished in the meantime.
+  #[default]
+  Counted,
+  /// Clocks pick up from exactly what they showed when last saved.
+  Paused,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct SavedClocks {
+  clocks: BTreeMap<String, SavedClock>,
+  /// The `SubathonLog` total.
+  #[serde(default)]
+  subathon_seconds: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SavedClock {
+  pub mode: ClockMode,
+  /// What the clock showed, time left for countdowns or time running for
+  /// stopwatches.
+  pub seconds: f32,
+  pub paused: bool,
+  pub overtime: bool,
+  pub finished: bool,
+  /// When a running countdown reaches zero.
+  pub end: Option<DateTime<Local>>,
+  pub saved_at: DateTime<Local>,
+  /// Scheduled multiplier windows that hadn't ended yet.
+  #[serde(default)]
+  pub multipliers: Vec<(MultiplierKind, MultiplierWindow)>,
+}
+
+pub(super) fn plugin(app: &mut App) {
+  app
+    .insert_resource(ClockStore::load(DEFAULT_CLOCKS_PATH))
+    .add_systems(Last, save_clocks)
+    .add_observer(restore_clock)
+    .add_observer(time_changed::<AddTime>)
+    .add_observer(time_changed::<PauseClock>)
+    .add_observer(time_changed::<ResumeClock>)
+    .add_observer(time_changed::<ResetClock>)
+    .add_observer(time_changed::<SetClock>)
+    .add_observer(time_changed::<ScheduleMultiplier>)
+    .add_observer(time_changed::<ClockFinished>)
+    .add_observer(time_changed::<ClockRestarted>);
+}
+
+fn restore_clock(
+  trigger: Trigger<OnAdd, ClockId>,
+  mut clocks: Query<(&ClockId, &mut Clock)>,
+  store: Res<ClockStore>,
+) {
+  if let Ok((id, mut clock)) = clocks.get_mut(trigger.target()) {
+    if let Some(saved) = store.get(&id.0) {
+      saved.apply(&mut clock, store.downtime);
+    }
+  }
+}
+
+/// Clocks are read when saving rather than here, as the event may not have
+/// reached them yet.
+fn time_changed<E: Event>(_trigger: Trigger<E>, mut store: ResMut<ClockStore>) {
+  store.dirty = true;
+}
+
+fn save_clocks(
+  clocks: Query<(&ClockId, &Clock)>,
+  log: Option<Res<SubathonLog>>,
+  mut store: ResMut<ClockStore>,
+  time: Res<Time>,
+) {
+  store.save_timer.tick(time.delta());
+  if !store.dirty && !store.save_timer.just_finished() {
+    return;
+  }
+
+  for (id, clock) in &clocks {
+    store
+      .saved
+      .clocks
+      .insert(id.0.clone(), SavedClock::read(clock));
+  }
+  if let Some(log) = log {
+    store.saved.subathon_seconds = log.total_seconds();
+  }
+  if let Err(err) = store.save() {
+    warn!("Failed to save clocks to {}: {}", store.path.display(), err);
+  }
+  store.dirty = false;
+}
+
+impl ClockStore {
+  /// Loads clocks from `path`. A missing file gives an empty store, and a
+  /// file that can't be parsed is moved aside so it isn't overwritten.
+  pub fn load<P: AsRef<Path>>(path: P) -> ClockStore {
+    let path = path.as_ref().to_path_buf();
+    ClockStore {
+      saved: load_ron(&path),
+      path,
+      downtime: ClockDowntime::default(),
+      save_timer: Timer::from_seconds(SAVE_INTERVAL_SECONDS, TimerMode::Repeating),
+      dirty: false,
+    }
+  }
+
+  pub fn with_downtime(mut self, downtime: ClockDowntime) -> ClockStore {
+    self.downtime = downtime;
+    self
+  }
+
+  pub fn get(&self, id: &str) -> Option<&SavedClock> {
+    self.saved.clocks.get(id)
+  }
+
+  pub fn remove(&mut self, id: &str) {
+    if self.saved.clocks.remove(id).is_some() {
+      self.dirty = true;
+    }
+  }
+
+  /// How much time support had added to the subathon when last saved.
+  pub fn subathon_seconds(&self) -> f32 {
+    self.saved.subathon_seconds
+  }
+
+  pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+    save_ron(&self.path, &self.saved)
+  }
+}
+
+impl SavedClock {
+  pub fn read(clock: &Clock) -> SavedClock {
+    let now = Local::now();
+    let seconds = clock.seconds();
+    let end = match clock.mode {
+      _ if clock.is_paused() => None,
+      ClockMode::Countdown => Some(now + TimeDelta::milliseconds((seconds * 1000.0) as i64)),
+      ClockMode::Stopwatch => None,
+      ClockMode::CountdownTo(target) => Some(target),
+    };
+
+    SavedClock {
+      mode: clock.mode,
+      seconds,
+      paused: clock.is_paused(),
+      overtime: clock.overtime,
+      finished: clock.finished,
+      end,
+      saved_at: now,
+      multipliers: clock
+        .multipliers
+        .iter()
+        .filter(|(_, window)| window.end() > now)
+        .copied()
+        .collect(),
+    }
+  }
+
+  /// Puts `clock` back how it was saved. Its format and finished actions
+  /// are left as they are, and saved multipliers are added to any it was
+  /// spawned with.
+  pub fn apply(&self, clock: &mut Clock, downtime: ClockDowntime) {
+    let now = Local::now();
+    let seconds = if self.paused || downtime == ClockDowntime::Paused {
+      self.seconds
+    } else {
+      match (self.mode, self.end) {
+        (ClockMode::Stopwatch, _) => self.seconds + seconds_between(self.saved_at, now),
+        (_, Some(end)) => seconds_between(now, end),
+        (_, None) => self.seconds,
+      }
+    };
+
+    clock.mode = self.mode;
+    clock.overtime = false;
+    clock.paused_at = None;
+    clock.set_seconds(seconds);
+    clock.overtime = self.overtime;
+    clock.finished = self.finished;
+    for multiplier in &self.multipliers {
+      if !clock.multipliers.contains(multiplier) {
+        clock.multipliers.push(*multiplier);
+      }
+    }
+    if self.paused {
+      clock.pause();
+    }
+  }
+}
+
+/// Never negative, in case the system clock was moved backwards.
+fn seconds_between(from: DateTime<Local>, to: DateTime<Local>) -> f32 {
+  (to - from).num_milliseconds().max(0) as f32 / 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use super::*;
+
+  /// Saves `clock` and then pretends the app was closed for `seconds` before
+  /// restoring it over a freshly made clock.
+  fn restart(clock: &Clock, seconds: i64, downtime: ClockDowntime) -> Clock {
+    let mut saved = SavedClock::read(clock);
+    let downtime_delta = TimeDelta::seconds(seconds);
+    saved.saved_at -= downtime_delta;
+    saved.end = saved.end.map(|end| end - downtime_delta);
+
+    let mut restored = Clock::new(3600.0);
+    saved.apply(&mut restored, downtime);
+    restored
+  }
+
+  fn assert_near(seconds: f32, expected: f32) {
+    assert!(
+      (seconds - expected).abs() < 0.1,
+      "{} is not {}",
+      seconds,
+      expected
+    );
+  }
+
+  fn countdown_to(seconds: i64) -> Clock {
+    Clock {
+      mode: ClockMode::CountdownTo(Local::now() + TimeDelta::seconds(seconds)),
+      ..Clock::new(0.0)
+    }
+  }
+
+  fn overtime(seconds: u64) -> Clock {
+    let mut clock = Clock {
+      mode: ClockMode::Stopwatch,
+      overtime: true,
+      finished: true,
+      ..Clock::new(0.0)
+    };
+    clock.elapsed.set_elapsed(Duration::from_secs(seconds));
+    clock
+  }
+
+  #[test]
+  fn countdown_counts_downtime() {
+    let restored = restart(&Clock::new(100.0), 60, ClockDowntime::Counted);
+    assert_eq!(restored.mode(), ClockMode::Countdown);
+    assert_near(restored.seconds(), 40.0);
+
+    // Closed for longer than it had left, so it has run out.
+    let restored = restart(&Clock::new(100.0), 200, ClockDowntime::Counted);
+    assert_eq!(restored.seconds(), 0.0);
+    assert!(restored.has_run_out());
+  }
+
+  #[test]
+  fn countdown_ignores_paused_downtime() {
+    let restored = restart(&Clock::new(100.0), 60, ClockDowntime::Paused);
+    assert_near(restored.seconds(), 100.0);
+  }
+
+  #[test]
+  fn countdown_to_counts_downtime() {
+    let restored = restart(&countdown_to(100), 60, ClockDowntime::Counted);
+    assert!(matches!(restored.mode(), ClockMode::CountdownTo(_)));
+    assert_near(restored.seconds(), 40.0);
+  }
+
+  #[test]
+  fn countdown_to_ignores_paused_downtime() {
+    let restored = restart(&countdown_to(100), 60, ClockDowntime::Paused);
+    assert!(matches!(restored.mode(), ClockMode::CountdownTo(_)));
+    assert_near(restored.seconds(), 100.0);
+  }
+
+  #[test]
+  fn overtime_counts_downtime() {
+    let restored = restart(&overtime(30), 60, ClockDowntime::Counted);
+    assert_eq!(restored.mode(), ClockMode::Stopwatch);
+    assert!(restored.overtime && restored.finished);
+    assert_near(restored.seconds(), 90.0);
+  }
+
+  #[test]
+  fn overtime_ignores_paused_downtime() {
+    let restored = restart(&overtime(30), 60, ClockDowntime::Paused);
+    assert!(restored.overtime);
+    assert_near(restored.seconds(), 30.0);
+  }
+
+  #[test]
+  fn paused_clocks_stay_paused() {
+    for downtime in [ClockDowntime::Counted, ClockDowntime::Paused] {
+      let mut clock = Clock::new(100.0);
+      clock.pause();
+      let restored = restart(&clock, 60, downtime);
+      assert!(restored.is_paused());
+      assert_near(restored.seconds(), 100.0);
+
+      let mut clock = countdown_to(100);
+      clock.pause();
+      let restored = restart(&clock, 60, downtime);
+      assert!(restored.is_paused());
+      assert_near(restored.seconds(), 100.0);
+    }
+  }
+
+  #[test]
+  fn multipliers_survive_restart() {
+    let happy_hour = MultiplierWindow::from_now(3600.0, 2.0);
+    let ended = MultiplierWindow::new(Local::now() - TimeDelta::hours(2), 3600.0, 3.0);
+    let clock = Clock::new(100.0)
+      .with_multiplier(MultiplierKind::Contribution, happy_hour)
+      .with_multiplier(MultiplierKind::TimeRate, ended);
+
+    let restored = restart(&clock, 60, ClockDowntime::Counted);
+    assert_eq!(restored.multiplier(MultiplierKind::Contribution), 2.0);
+    assert_eq!(
+      restored.multipliers,
+      [(MultiplierKind::Contribution, happy_hour)]
+    );
+
+    // Restoring over a clock spawned with the same window doesn't double it.
+    let mut spawned = Clock::new(3600.0).with_multiplier(MultiplierKind::Contribution, happy_hour);
+    SavedClock::read(&clock).apply(&mut spawned, ClockDowntime::Counted);
+    assert_eq!(spawned.multiplier(MultiplierKind::Contribution), 2.0);
+  }
+
+  #[test]
+  fn store_survives_restart() {
+    let path = std::env::temp_dir().join(format!("clocks-test-{}.ron", std::process::id()));
+    let mut store = ClockStore::load(&path);
+    store
+      .saved
+      .clocks
+      .insert("subathon".to_string(), SavedClock::read(&Clock::new(100.0)));
+    store.saved.subathon_seconds = 250.0;
+    store.save().unwrap();
+
+    let store = ClockStore::load(&path);
+    let _ = std::fs::remove_file(&path);
+    let mut restored = Clock::new(3600.0);
+    store
+      .get("subathon")
+      .unwrap()
+      .apply(&mut restored, ClockDowntime::Counted);
+    assert_near(restored.seconds(), 100.0);
+    assert_eq!(store.subathon_seconds(), 250.0);
+  }
+}
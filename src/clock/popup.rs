@@ -0,0 +1,150 @@
+use bevy::{
+  color::palettes::css::{LIME, RED},
+  prelude::*,
+};
+
+use super::ClockTimeAdded;
+use crate::draggable_interface::crisp_text::CrispText;
+
+/// Popups are this much smaller than the clock's own text.
+const POPUP_FONT_SCALE: f32 = 0.75;
+
+/// Shows a "+30s" or "-10s" popup floating out of a clock whenever time is
+/// added to it or taken off.
+#[derive(Component, Clone, Debug)]
+pub struct TimePopups {
+  pub added_color: Color,
+  pub removed_color: Color,
+  /// How far popups float up, in pixels.
+  pub rise: f32,
+  /// How long popups take to float up and fade away.
+  pub seconds: f32,
+}
+
+#[derive(Component)]
+struct TimePopup {
+  start: Vec3,
+  rise: f32,
+  color: Color,
+  timer: Timer,
+}
+
+pub(super) fn plugin(app: &mut App) {
+  app
+    .add_systems(Update, animate_popups)
+    .add_observer(spawn_popup);
+}
+
+fn spawn_popup(
+  trigger: Trigger<ClockTimeAdded>,
+  clocks: Query<(&TimePopups, &TextFont)>,
+  mut commands: Commands,
+) {
+  let Ok((popups, text_font)) = clocks.get(trigger.entity) else {
+    return;
+  };
+  let Some(text) = popup_text(trigger.seconds) else {
+    return;
+  };
+
+  let color = if trigger.seconds > 0.0 {
+    popups.added_color
+  } else {
+    popups.removed_color
+  };
+  // Starts just above the clock so it doesn't cover the time.
+  let start = Vec3::new(0.0, text_font.font_size, 1.0);
+
+  commands.spawn((
+    Text2d::new(text),
+    text_font
+      .clone()
+      .with_font_size(text_font.font_size * POPUP_FONT_SCALE),
+    TextColor(color),
+    CrispText::default(),
+    Transform::from_translation(start),
+    TimePopup {
+      start,
+      rise: popups.rise,
+      color,
+      timer: Timer::from_seconds(popups.seconds, TimerMode::Once),
+    },
+    ChildOf(trigger.entity),
+  ));
+}
+
+fn animate_popups(
+  mut popups: Query<(Entity, &mut TimePopup, &mut Transform, &mut TextColor)>,
+  time: Res<Time>,
+  mut commands: Commands,
+) {
+  for (entity, mut popup, mut transform, mut text_color) in &mut popups {
+    popup.timer.tick(time.delta());
+
+    let t = popup.timer.fraction();
+    let rise = EaseFunction::CubicOut.sample_clamped(t) * popup.rise;
+    transform.translation = popup.start + Vec3::Y * rise;
+    // Stays solid for the first half so it can be read.
+    let alpha = 1.0 - EaseFunction::CubicIn.sample_clamped((t * 2.0 - 1.0).max(0.0));
+    text_color.0 = popup.color.with_alpha(popup.color.alpha() * alpha);
+
+    if popup.timer.finished() {
+      commands.entity(entity).despawn();
+    }
+  }
+}
+
+/// `+1h 5m`, `+5m 30s` or `-10s`, or `None` if it rounds to nothing.
+fn popup_text(seconds: f32) -> Option<String> {
+  let sign = if seconds < 0.0 { '-' } else { '+' };
+  let total = seconds.abs().round() as u64;
+  if total == 0 {
+    return None;
+  }
+
+  let parts = [
+    (total / 3600, "h"),
+    (total % 3600 / 60, "m"),
+    (total % 60, "s"),
+  ];
+  let text = parts
+    .iter()
+    .filter(|(value, _)| *value != 0)
+    .map(|(value, unit)| format!("{}{}", value, unit))
+    .collect::<Vec<_>>()
+    .join(" ");
+  Some(format!("{}{}", sign, text))
+}
+
+impl TimePopups {
+  pub fn new() -> TimePopups {
+    TimePopups::default()
+  }
+
+  pub fn with_colors<A: Into<Color>, R: Into<Color>>(mut self, added: A, removed: R) -> TimePopups {
+    self.added_color = added.into();
+    self.removed_color = removed.into();
+    self
+  }
+
+  pub fn with_rise(mut self, rise: f32) -> TimePopups {
+    self.rise = rise;
+    self
+  }
+
+  pub fn with_seconds(mut self, seconds: f32) -> TimePopups {
+    self.seconds = seconds;
+    self
+  }
+}
+
+impl Default for TimePopups {
+  fn default() -> Self {
+    TimePopups {
+      added_color: LIME.into(),
+      removed_color: RED.into(),
+      rise: 40.0,
+      seconds: 1.5,
+    }
+  }
+}
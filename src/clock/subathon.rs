@@ -0,0 +1,296 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use chrono::{DateTime, Local};
+
+use super::{persistence::ClockStore, AddTime, Clock, ClockId, ClockTarget, MultiplierKind};
+
+/// Someone supporting the stream, which adds time to the subathon clock
+/// according to the `SubathonRules`.
+#[derive(Event, Clone, Debug)]
+pub struct Support {
+  pub from: String,
+  pub kind: SupportKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SupportKind {
+  Follow,
+  /// A new subscription or a resubscription.
+  Subscription(SubTier),
+  GiftSubscription {
+    tier: SubTier,
+    count: u32,
+  },
+  Bits(u32),
+  Raid {
+    viewers: u32,
+  },
+  /// A donation, such as through Ko-fi, in whole currency units.
+  Donation(f32),
+}
+
+/// Prime subscriptions count as tier one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SubTier {
+  #[default]
+  One,
+  Two,
+  Three,
+}
+
+/// How many seconds each kind of support adds, and to which clock. Anything
+/// without a rule adds nothing.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct SubathonRules {
+  pub clock: ClockTarget,
+  pub follow: f32,
+  /// Seconds for a tier one, two and three subscription.
+  pub subscription: [f32; 3],
+  /// Seconds for each gifted tier one, two and three subscription.
+  pub gift_subscription: [f32; 3],
+  pub bits_per_100: f32,
+  pub raid_per_viewer: f32,
+  pub donation_per_unit: f32,
+  /// The most a single event can add, including any contribution
+  /// multiplier.
+  pub max_per_event: Option<f32>,
+  /// The most that can be added over the whole subathon, after which support
+  /// is still logged but adds nothing.
+  pub cap: Option<f32>,
+}
+
+/// Everyone who has added time, in the order they did. Only the total is
+/// kept when the app restarts, saved along with the clocks in `ClockStore`.
+#[derive(Resource, Default)]
+pub struct SubathonLog {
+  contributions: Vec<Contribution>,
+  total_seconds: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Contribution {
+  pub from: String,
+  pub kind: SupportKind,
+  /// What was actually added, after the contribution multiplier, the per
+  /// event max and the cap.
+  pub seconds: f32,
+  pub at: DateTime<Local>,
+}
+
+pub(super) fn plugin(app: &mut App) {
+  app
+    .init_resource::<SubathonRules>()
+    .init_resource::<SubathonLog>()
+    .add_systems(Startup, restore_total)
+    .add_observer(add_support_time);
+}
+
+/// Carries on from the saved total, otherwise restarting would reset the
+/// cap while the clock kept the time that was added.
+fn restore_total(store: Option<Res<ClockStore>>, mut log: ResMut<SubathonLog>) {
+  if let Some(store) = store {
+    log.total_seconds = store.subathon_seconds();
+  }
+}
+
+/// The clock's contribution multiplier is applied here rather than by
+/// `AddTime`, so a happy hour can't push past the max or the cap.
+fn add_support_time(
+  trigger: Trigger<Support>,
+  rules: Res<SubathonRules>,
+  clocks: Query<(Entity, &Clock, Option<&ClockId>, Option<&ChildOf>)>,
+  mut log: ResMut<SubathonLog>,
+  mut commands: Commands,
+) {
+  let support = trigger.event();
+  let mut seconds = rules.seconds_for(support.kind) * contribution_multiplier(&rules, &clocks);
+  if let Some(max_per_event) = rules.max_per_event {
+    seconds = seconds.min(max_per_event);
+  }
+  if let Some(cap) = rules.cap {
+    seconds = seconds.min(cap - log.total_seconds);
+  }
+  let seconds = seconds.max(0.0);
+
+  if seconds > 0.0 {
+    commands.trigger(
+      AddTime::new(seconds)
+        .with_target(rules.clock.clone())
+        .without_multipliers(),
+    );
+  }
+
+  log.total_seconds += seconds;
+  log.contributions.push(Contribution {
+    from: support.from.clone(),
+    kind: support.kind,
+    seconds,
+    at: Local::now(),
+  });
+}
+
+/// The highest multiplier of the clocks the rules add time to, `1.0` when
+/// there are none.
+fn contribution_multiplier(
+  rules: &SubathonRules,
+  clocks: &Query<(Entity, &Clock, Option<&ClockId>, Option<&ChildOf>)>,
+) -> f32 {
+  clocks
+    .iter()
+    .filter(|(entity, _, id, child_of)| rules.clock.matches(*entity, *id, *child_of))
+    .map(|(_, clock, ..)| clock.multiplier(MultiplierKind::Contribution))
+    .reduce(f32::max)
+    .unwrap_or(1.0)
+}
+
+impl Support {
+  pub fn new<S: Into<String>>(from: S, kind: SupportKind) -> Support {
+    Support {
+      from: from.into(),
+      kind,
+    }
+  }
+}
+
+impl SubTier {
+  fn index(self) -> usize {
+    match self {
+      SubTier::One => 0,
+      SubTier::Two => 1,
+      SubTier::Three => 2,
+    }
+  }
+}
+
+impl SubathonRules {
+  /// Rules adding time to `clock`, with nothing worth any time yet.
+  pub fn new(clock: ClockTarget) -> SubathonRules {
+    SubathonRules { clock, ..default() }
+  }
+
+  pub fn with_follow(mut self, seconds: f32) -> SubathonRules {
+    self.follow = seconds;
+    self
+  }
+
+  pub fn with_subscription(mut self, tier: SubTier, seconds: f32) -> SubathonRules {
+    self.subscription[tier.index()] = seconds;
+    self
+  }
+
+  /// Seconds for each subscription gifted at `tier`.
+  pub fn with_gift_subscription(mut self, tier: SubTier, seconds: f32) -> SubathonRules {
+    self.gift_subscription[tier.index()] = seconds;
+    self
+  }
+
+  /// Seconds for every 100 bits, smaller cheers add a share of it.
+  pub fn with_bits(mut self, seconds_per_100: f32) -> SubathonRules {
+    self.bits_per_100 = seconds_per_100;
+    self
+  }
+
+  pub fn with_raid(mut self, seconds_per_viewer: f32) -> SubathonRules {
+    self.raid_per_viewer = seconds_per_viewer;
+    self
+  }
+
+  pub fn with_donation(mut self, seconds_per_unit: f32) -> SubathonRules {
+    self.donation_per_unit = seconds_per_unit;
+    self
+  }
+
+  pub fn with_max_per_event(mut self, seconds: f32) -> SubathonRules {
+    self.max_per_event = Some(seconds);
+    self
+  }
+
+  pub fn with_cap(mut self, seconds: f32) -> SubathonRules {
+    self.cap = Some(seconds);
+    self
+  }
+
+  /// The seconds `kind` is worth before any max or cap.
+  pub fn seconds_for(&self, kind: SupportKind) -> f32 {
+    match kind {
+      SupportKind::Follow => self.follow,
+      SupportKind::Subscription(tier) => self.subscription[tier.index()],
+      SupportKind::GiftSubscription { tier, count } => {
+        self.gift_subscription[tier.index()] * count as f32
+      }
+      SupportKind::Bits(bits) => self.bits_per_100 * bits as f32 / 100.0,
+      SupportKind::Raid { viewers } => self.raid_per_viewer * viewers as f32,
+      SupportKind::Donation(amount) => self.donation_per_unit * amount,
+    }
+  }
+}
+
+impl SubathonLog {
+  pub fn contributions(&self) -> &[Contribution] {
+    &self.contributions
+  }
+
+  pub fn total_seconds(&self) -> f32 {
+    self.total_seconds
+  }
+
+  /// How much each person has added in total.
+  pub fn totals(&self) -> BTreeMap<&str, f32> {
+    let mut totals = BTreeMap::new();
+    for contribution in &self.contributions {
+      *totals.entry(contribution.from.as_str()).or_default() += contribution.seconds;
+    }
+    totals
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::clock::{add_time, MultiplierWindow};
+
+  fn support(app: &mut App, count: usize) {
+    for _ in 0..count {
+      app
+        .world_mut()
+        .trigger(Support::new("viewer", SupportKind::Follow));
+      app.world_mut().flush();
+    }
+  }
+
+  #[test]
+  fn multiplier_is_applied_before_the_max_and_cap() {
+    let mut app = App::new();
+    app
+      .add_plugins(plugin)
+      .add_observer(add_time)
+      .insert_resource(
+        SubathonRules::new(ClockTarget::All)
+          .with_follow(100.0)
+          .with_max_per_event(150.0)
+          .with_cap(250.0),
+      );
+    let clock = app
+      .world_mut()
+      .spawn(Clock::new(100.0).with_multiplier(
+        MultiplierKind::Contribution,
+        MultiplierWindow::from_now(3600.0, 2.0),
+      ))
+      .id();
+
+    support(&mut app, 3);
+
+    let log = app.world().resource::<SubathonLog>();
+    let added = log
+      .contributions()
+      .iter()
+      .map(|contribution| contribution.seconds)
+      .collect::<Vec<_>>();
+    assert_eq!(added, [150.0, 100.0, 0.0]);
+    assert_eq!(log.total_seconds(), 250.0);
+
+    let clock = app.world().get::<Clock>(clock).unwrap();
+    assert!((clock.time_left() - 350.0).abs() < 0.1);
+  }
+}
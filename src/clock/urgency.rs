@@ -0,0 +1,102 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use super::{Clock, ClockMode};
+
+/// How many times a second urgent clocks pulse.
+const PULSES_PER_SECOND: f32 = 1.0;
+
+/// Restyles a countdown as it runs low, such as turning red under a minute.
+/// Stopwatches and clocks in overtime keep their normal style.
+#[derive(Component, Clone, Debug, Default)]
+pub struct ClockUrgency {
+  styles: Vec<UrgencyStyle>,
+  /// The clock's own colour, put back once it is no longer urgent.
+  normal_color: Option<Color>,
+}
+
+/// Used while a countdown has less than `below` seconds left. When several
+/// apply the one with the lowest `below` wins.
+#[derive(Clone, Copy, Debug)]
+pub struct UrgencyStyle {
+  pub below: f32,
+  pub color: Option<Color>,
+  /// How much bigger the clock grows at the top of each pulse, `0.1` for
+  /// ten percent.
+  pub pulse: f32,
+}
+
+pub(super) fn plugin(app: &mut App) {
+  app.add_systems(Update, style_urgent_clocks);
+}
+
+fn style_urgent_clocks(
+  mut clocks: Query<(&Clock, &mut ClockUrgency, &mut TextColor, &mut Transform)>,
+  time: Res<Time>,
+) {
+  for (clock, mut urgency, mut text_color, mut transform) in &mut clocks {
+    let Some(style) = urgency.style_for(clock) else {
+      if let Some(color) = urgency.normal_color.take() {
+        text_color.0 = color;
+        transform.scale = Vec3::ONE;
+      }
+      continue;
+    };
+
+    urgency.normal_color.get_or_insert(text_color.0);
+    if let Some(color) = style.color {
+      text_color.0 = color;
+    } else if let Some(color) = urgency.normal_color {
+      text_color.0 = color;
+    }
+
+    let wave = (time.elapsed_secs() * PULSES_PER_SECOND * TAU).sin() * 0.5 + 0.5;
+    transform.scale = Vec3::splat(1.0 + style.pulse * wave);
+  }
+}
+
+impl ClockUrgency {
+  pub fn new() -> ClockUrgency {
+    ClockUrgency::default()
+  }
+
+  pub fn with_style(mut self, style: UrgencyStyle) -> ClockUrgency {
+    self.styles.push(style);
+    self
+  }
+
+  fn style_for(&self, clock: &Clock) -> Option<UrgencyStyle> {
+    if clock.mode() == ClockMode::Stopwatch {
+      return None;
+    }
+
+    let seconds = clock.seconds();
+    self
+      .styles
+      .iter()
+      .filter(|style| seconds < style.below)
+      .min_by(|a, b| a.below.total_cmp(&b.below))
+      .copied()
+  }
+}
+
+impl UrgencyStyle {
+  pub fn below(seconds: f32) -> UrgencyStyle {
+    UrgencyStyle {
+      below: seconds,
+      color: None,
+      pulse: 0.0,
+    }
+  }
+
+  pub fn with_color<C: Into<Color>>(mut self, color: C) -> UrgencyStyle {
+    self.color = Some(color.into());
+    self
+  }
+
+  pub fn with_pulse(mut self, pulse: f32) -> UrgencyStyle {
+    self.pulse = pulse;
+    self
+  }
+}
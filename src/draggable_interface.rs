@@ -1,48 +1,202 @@
-use bevy::{
-  input::mouse::{MouseButtonInput, MouseWheel},
-  prelude::*,
-};
+use bevy::{input::mouse::MouseWheel, prelude::*, render::primitives::Aabb};
+use camera::DragCameras;
+use constraints::{Constraints, DragBounds, Locked};
+use mode::OverlayMode;
+use serde::{Deserialize, Serialize};
+use snapping::Snapper;
+use ui::NodeMover;
+
+pub mod arrange;
+pub mod camera;
+pub mod constraints;
+pub mod crisp_text;
+pub mod history;
+pub mod mode;
+pub mod persistence;
+pub mod profiles;
+pub mod resize;
+pub mod selection;
+pub mod snapping;
+mod ui;
+
+const DEFAULT_MIN_SCALE: f32 = 0.01;
 
 #[derive(Component)]
+#[component(on_add = constraints::remember_spawn_layout)]
 pub struct DraggableInterface {
+  id: Option<String>,
   scale_factor: f32,
+  min_scale: f32,
+  max_scale: f32,
+  lock_aspect_ratio: bool,
+  bounds: Option<DragBounds>,
+  /// Translation at the start of a drag, or the `left` and `top` offsets for
+  /// UI nodes.
+  drag_start: Vec2,
+}
+
+/// Everything the user can change about where a widget sits. UI nodes are
+/// placed by their `left` and `top` offsets rather than their translation.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct WidgetLayout {
+  pub translation: Vec3,
+  pub scale: Vec3,
+  #[serde(default)]
+  pub rotation: Quat,
+  #[serde(default)]
+  pub left: Option<Val>,
+  #[serde(default)]
+  pub top: Option<Val>,
+}
+
+/// Triggered on a draggable entity whenever the user has finished moving,
+/// resizing, rotating or reordering it.
+#[derive(Event, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LayoutChanged {
+  Moved,
+  Zoomed,
+  Resized,
+  Rotated,
+  Reordered,
+  /// Put back where it was spawned.
+  Reset,
+  /// Moved by switching layout profile. Not undoable, history just starts
+  /// again from the new layout.
+  ProfileApplied,
+  /// Put back by undo or redo, so it isn't recorded as a new edit.
+  Restored,
 }
 
 #[derive(Component)]
 struct Selected;
 
 #[derive(Component)]
-struct PendingUnselect;
+struct Hovered;
+
+/// Where each 2D draggable is, for working out what lines up with or lies
+/// inside what.
+type WidgetBounds<'w, 's> = Query<
+  'w,
+  's,
+  (
+    Entity,
+    &'static GlobalTransform,
+    Option<&'static Aabb>,
+    Has<Selected>,
+  ),
+  (With<DraggableInterface>, Without<Node>),
+>;
+
+type Zoomable<'w, 's> = Query<
+  'w,
+  's,
+  (
+    Entity,
+    &'static mut Transform,
+    &'static DraggableInterface,
+    Has<Selected>,
+    Has<Hovered>,
+    Has<Node>,
+  ),
+  Without<Locked>,
+>;
+
+type DragStarts<'w, 's> = Query<
+  'w,
+  's,
+  (
+    Entity,
+    &'static mut DraggableInterface,
+    &'static Transform,
+    Option<&'static Node>,
+    Has<Selected>,
+  ),
+  Without<Locked>,
+>;
+
+type Movable<'w, 's> = Query<
+  'w,
+  's,
+  (
+    Entity,
+    &'static DraggableInterface,
+    &'static mut Transform,
+    Option<&'static mut Node>,
+    Has<Selected>,
+  ),
+  Without<Locked>,
+>;
+
+type DragEnds<'w, 's> =
+  Query<'w, 's, (Entity, Has<Selected>), (With<DraggableInterface>, Without<Locked>)>;
 
 pub(super) fn plugin(app: &mut App) {
   app
-    .add_systems(Update, mouse_up)
-    .add_systems(Update, zoom)
+    .add_plugins((
+      arrange::plugin,
+      constraints::plugin,
+      crisp_text::plugin,
+      history::plugin,
+      mode::plugin,
+      persistence::plugin,
+      profiles::plugin,
+      resize::plugin,
+      selection::plugin,
+      snapping::plugin,
+    ))
+    // Draggables work without `UiPlugin`, which is what normally adds this.
+    .init_resource::<UiScale>()
+    .add_systems(Update, zoom.run_if(in_state(OverlayMode::Edit)))
     .add_observer(draggable_added);
 }
 
-fn mouse_up(
-  mouse_buttons: Res<ButtonInput<MouseButton>>,
-  mut unselected_entites: Query<Entity, With<PendingUnselect>>,
+/// Zooms every selected widget around their shared centre, or the widget
+/// under the pointer when nothing is selected. UI nodes are scaled in place
+/// through their `Transform`, since layout only overrides its translation.
+fn zoom(
+  mut scroll_event: EventReader<MouseWheel>,
+  mut draggables: Zoomable,
+  keys: Res<ButtonInput<KeyCode>>,
   mut commands: Commands,
 ) {
-  if mouse_buttons.just_released(MouseButton::Left) {
-    for entity in &mut unselected_entites {
-      commands
-        .entity(entity)
-        .remove::<Selected>()
-        .remove::<PendingUnselect>();
-    }
+  if keys.any_pressed(arrange::ROTATE_KEYS) {
+    scroll_event.clear();
+    return;
   }
-}
 
-fn zoom(
-  mut scroll_event: EventReader<MouseWheel>,
-  mut transforms: Query<(&mut Transform, &DraggableInterface), With<Selected>>,
-) {
   for scroll in scroll_event.read() {
-    for (mut transform, draggable) in &mut transforms {
-      transform.scale += scroll.y * draggable.scale_factor;
+    let any_selected = draggables.iter().any(|(_, _, _, selected, ..)| selected);
+    let is_target = |selected: bool, hovered: bool| {
+      if any_selected {
+        selected
+      } else {
+        hovered
+      }
+    };
+
+    let world_targets = draggables
+      .iter()
+      .filter(|(_, _, _, selected, hovered, is_node)| !is_node && is_target(*selected, *hovered))
+      .map(|(_, transform, ..)| transform.translation.truncate())
+      .collect::<Vec<_>>();
+    let center = world_targets.iter().sum::<Vec2>() / world_targets.len().max(1) as f32;
+
+    for (entity, mut transform, draggable, selected, hovered, is_node) in &mut draggables {
+      if !is_target(selected, hovered) {
+        continue;
+      }
+
+      let old_scale = transform.scale;
+      transform.scale = draggable.clamp_scale(old_scale + scroll.y * draggable.scale_factor);
+      if is_node {
+        commands.trigger_targets(LayoutChanged::Zoomed, entity);
+        continue;
+      }
+
+      let ratio = transform.scale.truncate() / old_scale.truncate();
+      let position = center + (transform.translation.truncate() - center) * ratio;
+      transform.translation = position.extend(transform.translation.z);
+      commands.trigger_targets(LayoutChanged::Zoomed, entity);
     }
   }
 }
@@ -50,33 +204,149 @@ fn zoom(
 fn draggable_added(trigger: Trigger<OnAdd, DraggableInterface>, mut commands: Commands) {
   commands
     .entity(trigger.target())
+    .observe(drag_started)
     .observe(get_dragged)
-    .observe(insert_selected_on::<Pointer<DragEnd>>())
-    .observe(insert_selected_on::<Pointer<Over>>())
-    .observe(remove_selected_on::<Pointer<Out>>());
+    .observe(drag_ended);
+}
+
+/// Dragging a selected widget moves the rest of the selection with it.
+fn moves_with(dragged: Entity, dragged_selected: bool, entity: Entity, selected: bool) -> bool {
+  entity == dragged || (dragged_selected && selected)
 }
 
-fn get_dragged(trigger: Trigger<Pointer<Drag>>, mut transform: Query<&mut Transform>) {
-  if let Ok(mut transform) = transform.get_mut(trigger.target()) {
-    transform.translation.x += trigger.delta.x;
-    transform.translation.y -= trigger.delta.y;
+fn drag_started(trigger: Trigger<Pointer<DragStart>>, mut draggables: DragStarts) {
+  let dragged = trigger.target();
+  let Ok((_, _, _, _, dragged_selected)) = draggables.get(dragged) else {
+    return;
+  };
+
+  for (entity, mut draggable, transform, node, selected) in &mut draggables {
+    if moves_with(dragged, dragged_selected, entity, selected) {
+      draggable.drag_start = match node {
+        Some(node) => ui::node_offsets(node),
+        None => transform.translation.truncate(),
+      };
+    }
   }
 }
 
-fn insert_selected_on<E>() -> impl Fn(Trigger<E>, Query<Entity, With<Selected>>, Commands) {
-  move |trigger, other_entities_selected, mut commands| {
-    if other_entities_selected.iter().count() == 0 {
-      commands.entity(trigger.target()).insert(Selected);
+/// World widgets follow the dragged widget's snapped offset, held back so
+/// none of them leave their bounds. UI nodes simply follow the pointer across
+/// the screen.
+fn get_dragged(
+  trigger: Trigger<Pointer<Drag>>,
+  mut draggables: Movable,
+  cameras: DragCameras,
+  constraints: Constraints,
+  nodes: NodeMover,
+  mut snapper: Snapper,
+) {
+  let dragged = trigger.target();
+  let Ok((_, draggable, transform, node, dragged_selected)) = draggables.get(dragged) else {
+    return;
+  };
+
+  let pointer = trigger.pointer_location.position;
+  let start = pointer - trigger.distance;
+  let world_offset = match node {
+    Some(_) => None,
+    None => {
+      let position = draggable.drag_start + cameras.drag_delta(dragged, start, pointer).truncate();
+      let offset = snapper.snap(dragged, position, transform) - draggable.drag_start;
+      Some(
+        draggables
+          .iter()
+          .filter(|(entity, _, _, node, selected)| {
+            node.is_none() && moves_with(dragged, dragged_selected, *entity, *selected)
+          })
+          .fold(offset, |offset, (entity, draggable, transform, ..)| {
+            constraints.clamp_offset(entity, draggable, draggable.drag_start, transform, offset)
+          }),
+      )
+    }
+  };
+
+  for (entity, draggable, mut transform, node, selected) in &mut draggables {
+    if !moves_with(dragged, dragged_selected, entity, selected) {
+      continue;
+    }
+
+    if let Some(mut node) = node {
+      nodes.drag(entity, &mut node, draggable.drag_start, trigger.distance);
+      continue;
     }
+
+    let offset = world_offset.unwrap_or_else(|| {
+      let offset = cameras.drag_delta(entity, start, pointer).truncate();
+      constraints.clamp_offset(entity, draggable, draggable.drag_start, &transform, offset)
+    });
+    let position = draggable.drag_start + offset;
+    transform.translation.x = position.x;
+    transform.translation.y = position.y;
   }
 }
 
-fn remove_selected_on<E>() -> impl Fn(Trigger<E>, Res<ButtonInput<MouseButton>>, Commands) {
-  move |trigger, mouse_buttons, mut commands| {
-    if mouse_buttons.pressed(MouseButton::Left) {
-      commands.entity(trigger.target()).insert(PendingUnselect);
-    } else {
-      commands.entity(trigger.target()).remove::<Selected>();
+fn drag_ended(trigger: Trigger<Pointer<DragEnd>>, draggables: DragEnds, mut commands: Commands) {
+  let dragged = trigger.target();
+  let Ok((_, dragged_selected)) = draggables.get(dragged) else {
+    return;
+  };
+
+  for (entity, selected) in &draggables {
+    if moves_with(dragged, dragged_selected, entity, selected) {
+      commands.trigger_targets(LayoutChanged::Moved, entity);
+    }
+  }
+}
+
+/// The world space bounds of a widget, or just its position if it has none.
+fn world_rect(global_transform: &GlobalTransform, aabb: Option<&Aabb>) -> Rect {
+  match aabb {
+    Some(aabb) => placed_rect(aabb, |point| global_transform.transform_point(point)),
+    None => {
+      let position = global_transform.translation().truncate();
+      Rect::from_corners(position, position)
+    }
+  }
+}
+
+/// The bounds of `aabb` once `place` has moved each of its corners, so a
+/// rotated widget is bounded by the box around its rotated corners.
+fn placed_rect(aabb: &Aabb, place: impl Fn(Vec3) -> Vec3) -> Rect {
+  let center = Vec3::from(aabb.center);
+  let half_extents = Vec3::from(aabb.half_extents);
+  let corner = |x: f32, y: f32| place(center + half_extents * Vec3::new(x, y, 0.0)).truncate();
+
+  let first = corner(-1.0, -1.0);
+  [corner(1.0, -1.0), corner(1.0, 1.0), corner(-1.0, 1.0)]
+    .into_iter()
+    .fold(Rect::from_corners(first, first), |rect, corner| {
+      rect.union_point(corner)
+    })
+}
+
+impl WidgetLayout {
+  pub fn read(transform: &Transform, node: Option<&Node>) -> WidgetLayout {
+    WidgetLayout {
+      translation: transform.translation,
+      scale: transform.scale,
+      rotation: transform.rotation,
+      left: node.map(|node| node.left),
+      top: node.map(|node| node.top),
+    }
+  }
+
+  pub fn apply(&self, transform: &mut Transform, node: Option<&mut Node>) {
+    transform.translation = self.translation;
+    transform.scale = self.scale;
+    transform.rotation = self.rotation;
+    if let Some(node) = node {
+      if let Some(left) = self.left {
+        node.left = left;
+      }
+      if let Some(top) = self.top {
+        node.top = top;
+      }
     }
   }
 }
@@ -90,10 +360,53 @@ impl DraggableInterface {
     self.scale_factor = factor;
     self
   }
+
+  /// Gives the draggable a stable id so its layout can be saved and restored
+  /// across restarts.
+  pub fn with_id<S: Into<String>>(mut self, id: S) -> DraggableInterface {
+    self.id = Some(id.into());
+    self
+  }
+
+  /// Keeps the scale between `min` and `max` when zooming or resizing. The
+  /// minimum is never allowed to reach zero so the widget can't flip.
+  pub fn with_scale_limits(mut self, min: f32, max: f32) -> DraggableInterface {
+    self.min_scale = min.max(f32::EPSILON);
+    self.max_scale = max.max(self.min_scale);
+    self
+  }
+
+  /// Keeps the widget inside `bounds` while it is dragged.
+  pub fn with_bounds(mut self, bounds: DragBounds) -> DraggableInterface {
+    self.bounds = Some(bounds);
+    self
+  }
+
+  /// Whether dragging a corner handle keeps the widget's aspect ratio.
+  pub fn with_locked_aspect_ratio(mut self, locked: bool) -> DraggableInterface {
+    self.lock_aspect_ratio = locked;
+    self
+  }
+
+  pub fn id(&self) -> Option<&str> {
+    self.id.as_deref()
+  }
+
+  pub fn clamp_scale(&self, scale: Vec3) -> Vec3 {
+    scale.clamp(Vec3::splat(self.min_scale), Vec3::splat(self.max_scale))
+  }
 }
 
 impl Default for DraggableInterface {
   fn default() -> Self {
-    DraggableInterface { scale_factor: 1.0 }
+    DraggableInterface {
+      id: None,
+      scale_factor: 1.0,
+      min_scale: DEFAULT_MIN_SCALE,
+      max_scale: f32::MAX,
+      lock_aspect_ratio: true,
+      bounds: None,
+      drag_start: Vec2::ZERO,
+    }
   }
 }
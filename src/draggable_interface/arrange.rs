@@ -0,0 +1,245 @@
+use bevy::{input::mouse::MouseWheel, prelude::*};
+
+use super::{
+  camera::DragCameras,
+  constraints::{Constraints, Locked},
+  mode::OverlayMode,
+  ui::{self, NodeMover},
+  DraggableInterface, LayoutChanged, Selected,
+};
+
+const NUDGE_DISTANCE: f32 = 1.0;
+const LARGE_NUDGE_DISTANCE: f32 = 10.0;
+/// How far one line of mouse wheel scroll rotates a widget.
+const ROTATION_STEP_DEGREES: f32 = 5.0;
+
+const SHIFT_KEYS: [KeyCode; 2] = [KeyCode::ShiftLeft, KeyCode::ShiftRight];
+/// Holding any of these turns the mouse wheel from zooming to rotating.
+pub(super) const ROTATE_KEYS: [KeyCode; 2] = SHIFT_KEYS;
+
+/// Moves every selected widget by this many logical pixels on screen, with y
+/// pointing up.
+#[derive(Event, Clone, Copy)]
+pub struct NudgeSelected(pub Vec2);
+
+/// Rotates every selected widget about its own origin, anticlockwise in
+/// radians.
+#[derive(Event, Clone, Copy)]
+pub struct RotateSelected(pub f32);
+
+/// Moves the selected widgets above, or below, every other widget.
+#[derive(Event, Clone, Copy)]
+pub enum ReorderSelected {
+  BringToFront,
+  SendToBack,
+}
+
+type Nudgeable<'w, 's> = Query<
+  'w,
+  's,
+  (
+    Entity,
+    &'static DraggableInterface,
+    &'static mut Transform,
+    Option<&'static mut Node>,
+  ),
+  (With<Selected>, Without<Locked>),
+>;
+
+type Rotatable<'w, 's> = Query<
+  'w,
+  's,
+  (Entity, &'static mut Transform),
+  (With<DraggableInterface>, With<Selected>, Without<Locked>),
+>;
+
+type Reorderable<'w, 's> = Query<
+  'w,
+  's,
+  (
+    Entity,
+    &'static mut Transform,
+    Option<&'static ZIndex>,
+    Has<Node>,
+    Has<Selected>,
+  ),
+  With<DraggableInterface>,
+>;
+
+pub(super) fn plugin(app: &mut App) {
+  app
+    .add_systems(
+      Update,
+      (arrange_keys, rotate_on_scroll).run_if(in_state(OverlayMode::Edit)),
+    )
+    .add_observer(nudge_selected)
+    .add_observer(rotate_selected)
+    .add_observer(reorder_selected);
+}
+
+fn arrange_keys(keys: Res<ButtonInput<KeyCode>>, mut commands: Commands) {
+  let direction = [
+    (KeyCode::ArrowLeft, Vec2::NEG_X),
+    (KeyCode::ArrowRight, Vec2::X),
+    (KeyCode::ArrowUp, Vec2::Y),
+    (KeyCode::ArrowDown, Vec2::NEG_Y),
+  ]
+  .into_iter()
+  .filter(|(key, _)| keys.just_pressed(*key))
+  .map(|(_, direction)| direction)
+  .sum::<Vec2>();
+
+  if direction != Vec2::ZERO {
+    let distance = if keys.any_pressed(SHIFT_KEYS) {
+      LARGE_NUDGE_DISTANCE
+    } else {
+      NUDGE_DISTANCE
+    };
+    commands.trigger(NudgeSelected(direction * distance));
+  }
+
+  if keys.just_pressed(KeyCode::PageUp) {
+    commands.trigger(ReorderSelected::BringToFront);
+  }
+  if keys.just_pressed(KeyCode::PageDown) {
+    commands.trigger(ReorderSelected::SendToBack);
+  }
+}
+
+fn rotate_on_scroll(
+  keys: Res<ButtonInput<KeyCode>>,
+  mut scroll_event: EventReader<MouseWheel>,
+  mut commands: Commands,
+) {
+  if !keys.any_pressed(ROTATE_KEYS) {
+    scroll_event.clear();
+    return;
+  }
+
+  for scroll in scroll_event.read() {
+    // Some platforms turn the wheel into horizontal scrolling while Shift is
+    // held.
+    let amount = if scroll.y != 0.0 { scroll.y } else { scroll.x };
+    commands.trigger(RotateSelected(
+      (amount * ROTATION_STEP_DEGREES).to_radians(),
+    ));
+  }
+}
+
+fn nudge_selected(
+  trigger: Trigger<NudgeSelected>,
+  mut selected: Nudgeable,
+  cameras: DragCameras,
+  constraints: Constraints,
+  nodes: NodeMover,
+  mut commands: Commands,
+) {
+  let screen_delta = Vec2::new(trigger.0.x, -trigger.0.y);
+
+  for (entity, draggable, mut transform, node) in &mut selected {
+    match node {
+      Some(mut node) => {
+        let start = ui::node_offsets(&node);
+        nodes.drag(entity, &mut node, start, screen_delta);
+      }
+      None => {
+        let delta = cameras.drag_delta(entity, Vec2::ZERO, screen_delta);
+        let offset = constraints.clamp_offset(
+          entity,
+          draggable,
+          transform.translation.truncate(),
+          &transform,
+          delta.truncate(),
+        );
+        transform.translation += offset.extend(0.0);
+      }
+    }
+    commands.trigger_targets(LayoutChanged::Moved, entity);
+  }
+}
+
+fn rotate_selected(
+  trigger: Trigger<RotateSelected>,
+  mut selected: Rotatable,
+  mut commands: Commands,
+) {
+  for (entity, mut transform) in &mut selected {
+    transform.rotate_z(trigger.0);
+    commands.trigger_targets(LayoutChanged::Rotated, entity);
+  }
+}
+
+/// World widgets are ordered by their z translation and UI nodes by their
+/// `ZIndex`. UI is always drawn over the world, so each kind is only
+/// reordered against its own.
+fn reorder_selected(
+  trigger: Trigger<ReorderSelected>,
+  mut draggables: Reorderable,
+  mut commands: Commands,
+) {
+  for ui_nodes in [false, true] {
+    let depth = |transform: &Transform, z_index: Option<&ZIndex>| {
+      if ui_nodes {
+        z_index.map_or(0, |z_index| z_index.0) as f32
+      } else {
+        transform.translation.z
+      }
+    };
+
+    let mut selected = Vec::new();
+    let mut others = Vec::new();
+    for (_, transform, z_index, is_node, is_selected) in &draggables {
+      if is_node != ui_nodes {
+        continue;
+      }
+      if is_selected {
+        selected.push(depth(transform, z_index));
+      } else {
+        others.push(depth(transform, z_index));
+      }
+    }
+
+    let Some(offset) = reorder_offset(&selected, &others, *trigger.event()) else {
+      continue;
+    };
+
+    for (entity, mut transform, z_index, is_node, is_selected) in &mut draggables {
+      if is_node != ui_nodes || !is_selected {
+        continue;
+      }
+
+      if ui_nodes {
+        let z_index = z_index.map_or(0, |z_index| z_index.0);
+        commands
+          .entity(entity)
+          .insert(ZIndex(z_index + offset as i32));
+      } else {
+        transform.translation.z += offset;
+      }
+      commands.trigger_targets(LayoutChanged::Reordered, entity);
+    }
+  }
+}
+
+/// How far the selection has to move along z to sit just above, or below,
+/// everything else while keeping its own order. `None` if it already does.
+fn reorder_offset(selected: &[f32], others: &[f32], order: ReorderSelected) -> Option<f32> {
+  if selected.is_empty() {
+    return None;
+  }
+
+  match order {
+    ReorderSelected::BringToFront => {
+      let highest_other = others.iter().copied().reduce(f32::max)?;
+      let lowest_selected = selected.iter().copied().fold(f32::MAX, f32::min);
+      let offset = highest_other + 1.0 - lowest_selected;
+      (offset > 0.0).then_some(offset)
+    }
+    ReorderSelected::SendToBack => {
+      let lowest_other = others.iter().copied().reduce(f32::min)?;
+      let highest_selected = selected.iter().copied().fold(f32::MIN, f32::max);
+      let offset = lowest_other - 1.0 - highest_selected;
+      (offset < 0.0).then_some(offset)
+    }
+  }
+}
@@ -0,0 +1,321 @@
+use bevy::{
+  ecs::system::SystemParam, math::Affine3A, prelude::*, render::view::RenderLayers,
+  window::PrimaryWindow,
+};
+
+/// Turns pointer movement on screen into movement in the space a draggable
+/// lives in, using whichever camera renders it.
+#[derive(SystemParam)]
+pub(super) struct DragCameras<'w, 's> {
+  cameras: Query<
+    'w,
+    's,
+    (
+      &'static Camera,
+      &'static GlobalTransform,
+      Option<&'static RenderLayers>,
+    ),
+  >,
+  entities: Query<
+    'w,
+    's,
+    (
+      &'static GlobalTransform,
+      Option<&'static RenderLayers>,
+      Option<&'static ChildOf>,
+    ),
+  >,
+  global_transforms: Query<'w, 's, &'static GlobalTransform>,
+  window: Query<'w, 's, &'static Window, With<PrimaryWindow>>,
+}
+
+impl DragCameras<'_, '_> {
+  /// How far `entity` has to move, in its parent's space, to stay under a
+  /// pointer that moved from `start` to `current` on screen.
+  pub(super) fn drag_delta(&self, entity: Entity, start: Vec2, current: Vec2) -> Vec3 {
+    let fallback = Vec3::new(current.x - start.x, start.y - current.y, 0.0);
+
+    let Ok((global_transform, layers, child_of)) = self.entities.get(entity) else {
+      return fallback;
+    };
+    let Some((camera, camera_transform)) = self.camera_for(layers) else {
+      return fallback;
+    };
+    let Some(world_delta) = world_drag_delta(
+      camera,
+      camera_transform,
+      global_transform.translation(),
+      start,
+      current,
+    ) else {
+      return fallback;
+    };
+
+    match child_of.and_then(|child_of| self.global_transforms.get(child_of.parent()).ok()) {
+      Some(parent) => parent.affine().inverse().transform_vector3(world_delta),
+      None => world_delta,
+    }
+  }
+
+  /// What the camera that renders `entity` can see, in the space of its
+  /// parent. Without a camera this is the primary window centred on the
+  /// origin.
+  pub(super) fn view_rect(&self, entity: Entity) -> Option<Rect> {
+    let fallback = || {
+      let window = self.window.single().ok()?;
+      Some(Rect::from_center_size(Vec2::ZERO, window.size()))
+    };
+
+    let Ok((_, layers, child_of)) = self.entities.get(entity) else {
+      return fallback();
+    };
+    let Some((camera, camera_transform)) = self.camera_for(layers) else {
+      return fallback();
+    };
+    let Some(viewport) = camera.logical_viewport_rect() else {
+      return fallback();
+    };
+    let to_parent = child_of
+      .and_then(|child_of| self.global_transforms.get(child_of.parent()).ok())
+      .map_or(Affine3A::IDENTITY, |parent| parent.affine().inverse());
+
+    let mut rect: Option<Rect> = None;
+    for corner in [
+      viewport.min,
+      Vec2::new(viewport.max.x, viewport.min.y),
+      viewport.max,
+      Vec2::new(viewport.min.x, viewport.max.y),
+    ] {
+      let Ok(world) = camera.viewport_to_world_2d(camera_transform, corner) else {
+        return fallback();
+      };
+      let point = to_parent.transform_point3(world.extend(0.0)).truncate();
+      rect = Some(rect.map_or(Rect::from_corners(point, point), |rect| {
+        rect.union_point(point)
+      }));
+    }
+    rect
+  }
+
+  /// The highest order active camera that can see the given render layers.
+  fn camera_for(&self, layers: Option<&RenderLayers>) -> Option<(&Camera, &GlobalTransform)> {
+    let default_layers = RenderLayers::default();
+    let layers = layers.unwrap_or(&default_layers);
+
+    self
+      .cameras
+      .iter()
+      .filter(|(camera, _, camera_layers)| {
+        camera.is_active && camera_layers.unwrap_or(&default_layers).intersects(layers)
+      })
+      .max_by_key(|(camera, _, _)| camera.order)
+      .map(|(camera, camera_transform, _)| (camera, camera_transform))
+  }
+}
+
+/// Projects both pointer positions onto the plane facing the camera through
+/// `origin` and returns the world space distance between them.
+pub fn world_drag_delta(
+  camera: &Camera,
+  camera_transform: &GlobalTransform,
+  origin: Vec3,
+  start: Vec2,
+  current: Vec2,
+) -> Option<Vec3> {
+  let plane = InfinitePlane3d::new(camera_transform.back());
+
+  let project = |position: Vec2| {
+    let ray = camera.viewport_to_world(camera_transform, position).ok()?;
+    let distance = ray.intersect_plane(origin, plane)?;
+    Some(ray.get_point(distance))
+  };
+
+  Some(project(current)? - project(start)?)
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+  use bevy::{
+    ecs::system::RunSystemOnce,
+    render::camera::{camera_system, ManualTextureViews},
+    window::{
+      PrimaryWindow, WindowCreated, WindowResized, WindowResolution, WindowScaleFactorChanged,
+    },
+  };
+
+  use super::*;
+
+  /// An 800x600 window for cameras to render to, with nothing behind it.
+  pub(in crate::draggable_interface) fn world_with_window() -> World {
+    let mut world = World::new();
+    world.init_resource::<Events<WindowResized>>();
+    world.init_resource::<Events<WindowCreated>>();
+    world.init_resource::<Events<WindowScaleFactorChanged>>();
+    world.init_resource::<Events<AssetEvent<Image>>>();
+    world.init_resource::<Assets<Image>>();
+    world.init_resource::<ManualTextureViews>();
+    world.spawn((
+      Window {
+        resolution: WindowResolution::new(800.0, 600.0),
+        ..default()
+      },
+      PrimaryWindow,
+    ));
+    world
+  }
+
+  pub(in crate::draggable_interface) fn spawn_camera(
+    world: &mut World,
+    zoom_scale: f32,
+    translation: Vec3,
+    layers: RenderLayers,
+  ) {
+    world.spawn((
+      Camera2d,
+      Projection::Orthographic(OrthographicProjection {
+        scale: zoom_scale,
+        ..OrthographicProjection::default_2d()
+      }),
+      Transform::from_translation(translation),
+      GlobalTransform::from_translation(translation),
+      layers,
+    ));
+  }
+
+  fn spawn_widget(world: &mut World, translation: Vec3) -> Entity {
+    world
+      .spawn((
+        Transform::from_translation(translation),
+        GlobalTransform::from_translation(translation),
+      ))
+      .id()
+  }
+
+  fn drag_delta(world: &mut World, entity: Entity, start: Vec2, current: Vec2) -> Vec3 {
+    world.run_system_once(camera_system).unwrap();
+    world
+      .run_system_once_with(
+        |In((entity, start, current)): In<(Entity, Vec2, Vec2)>, cameras: DragCameras| {
+          cameras.drag_delta(entity, start, current)
+        },
+        (entity, start, current),
+      )
+      .unwrap()
+  }
+
+  fn assert_near(delta: Vec3, expected: Vec3) {
+    assert!(
+      delta.abs_diff_eq(expected, 1e-3),
+      "{} is not {}",
+      delta,
+      expected
+    );
+  }
+
+  #[test]
+  fn zoomed_camera_divides_by_zoom() {
+    let mut world = world_with_window();
+    // Zoomed in twice over, so the world moves half as far as the pointer.
+    spawn_camera(&mut world, 0.5, Vec3::ZERO, RenderLayers::default());
+    let widget = spawn_widget(&mut world, Vec3::ZERO);
+
+    let delta = drag_delta(
+      &mut world,
+      widget,
+      Vec2::new(400.0, 300.0),
+      Vec2::new(410.0, 290.0),
+    );
+    assert_near(delta, Vec3::new(5.0, 5.0, 0.0));
+  }
+
+  #[test]
+  fn translated_camera_keeps_delta() {
+    let mut world = world_with_window();
+    let translation = Vec3::new(1000.0, -500.0, 0.0);
+    spawn_camera(&mut world, 1.0, translation, RenderLayers::default());
+    let widget = spawn_widget(&mut world, translation + Vec3::new(50.0, 20.0, 0.0));
+
+    let delta = drag_delta(
+      &mut world,
+      widget,
+      Vec2::new(100.0, 100.0),
+      Vec2::new(130.0, 80.0),
+    );
+    assert_near(delta, Vec3::new(30.0, 20.0, 0.0));
+  }
+
+  #[test]
+  fn child_delta_is_in_parent_space() {
+    let mut world = world_with_window();
+    spawn_camera(&mut world, 1.0, Vec3::ZERO, RenderLayers::default());
+    let parent_transform = Transform::from_scale(Vec3::splat(2.0));
+    let parent = world
+      .spawn((parent_transform, GlobalTransform::from(parent_transform)))
+      .id();
+    let child = world
+      .spawn((
+        Transform::default(),
+        GlobalTransform::from(parent_transform),
+        ChildOf(parent),
+      ))
+      .id();
+
+    let delta = drag_delta(
+      &mut world,
+      child,
+      Vec2::new(400.0, 300.0),
+      Vec2::new(420.0, 300.0),
+    );
+    assert_near(delta, Vec3::new(10.0, 0.0, 0.0));
+  }
+
+  #[test]
+  fn hidden_layers_fall_back_to_screen_delta() {
+    let mut world = world_with_window();
+    spawn_camera(&mut world, 0.5, Vec3::ZERO, RenderLayers::layer(0));
+    let widget = world
+      .spawn((
+        Transform::default(),
+        GlobalTransform::default(),
+        RenderLayers::layer(1),
+      ))
+      .id();
+    let (start, current) = (Vec2::new(400.0, 300.0), Vec2::new(410.0, 290.0));
+
+    // No camera can see it, so the pointer's movement is used as is.
+    assert_near(
+      drag_delta(&mut world, widget, start, current),
+      Vec3::new(10.0, 10.0, 0.0),
+    );
+
+    // Once one can, that camera is used rather than the one that can't.
+    spawn_camera(&mut world, 2.0, Vec3::ZERO, RenderLayers::layer(1));
+    assert_near(
+      drag_delta(&mut world, widget, start, current),
+      Vec3::new(20.0, 20.0, 0.0),
+    );
+  }
+
+  #[test]
+  fn view_rect_follows_camera() {
+    let mut world = world_with_window();
+    spawn_camera(
+      &mut world,
+      0.5,
+      Vec3::new(100.0, 50.0, 0.0),
+      RenderLayers::default(),
+    );
+    let widget = spawn_widget(&mut world, Vec3::ZERO);
+
+    world.run_system_once(camera_system).unwrap();
+    let view = world
+      .run_system_once_with(
+        |In(entity): In<Entity>, cameras: DragCameras| cameras.view_rect(entity),
+        widget,
+      )
+      .unwrap()
+      .unwrap();
+    assert_near(view.min.extend(0.0), Vec3::new(-100.0, -100.0, 0.0));
+    assert_near(view.max.extend(0.0), Vec3::new(300.0, 200.0, 0.0));
+  }
+}
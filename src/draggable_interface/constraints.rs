@@ -0,0 +1,137 @@
+use bevy::{
+  ecs::{component::HookContext, system::SystemParam, world::DeferredWorld},
+  prelude::*,
+  render::primitives::Aabb,
+};
+
+use super::{
+  camera::DragCameras, placed_rect, DraggableInterface, LayoutChanged, Selected, WidgetLayout,
+};
+
+/// Stops a draggable from being dragged, zoomed, resized, nudged or rotated,
+/// and keeps it where it is when the selection is reset, edits are undone or
+/// redone and layout profiles are switched.
+#[derive(Component)]
+pub struct Locked;
+
+/// Where a draggable is allowed to be dragged to. Only applies to 2D widgets,
+/// in the space of their parent.
+#[derive(Clone, Copy, Debug)]
+pub enum DragBounds {
+  /// Whatever the camera that renders the widget can see, or the primary
+  /// window centred on the origin when no camera can.
+  Window,
+  Rect(Rect),
+}
+
+/// Puts every selected widget back where it was spawned.
+#[derive(Event, Clone, Copy)]
+pub struct ResetSelected;
+
+/// The layout a draggable had when it was spawned, before any saved layout
+/// was restored over it.
+#[derive(Component)]
+struct SpawnLayout(WidgetLayout);
+
+/// Keeps dragged widgets inside their `DragBounds`.
+#[derive(SystemParam)]
+pub(super) struct Constraints<'w, 's> {
+  aabbs: Query<'w, 's, &'static Aabb>,
+  cameras: DragCameras<'w, 's>,
+}
+
+type Resettable<'w, 's> = Query<
+  'w,
+  's,
+  (
+    Entity,
+    &'static SpawnLayout,
+    &'static mut Transform,
+    Option<&'static mut Node>,
+  ),
+  (With<DraggableInterface>, With<Selected>, Without<Locked>),
+>;
+
+pub(super) fn plugin(app: &mut App) {
+  app.add_observer(reset_selected);
+}
+
+/// Runs as a hook rather than an observer so it is guaranteed to see the
+/// spawn transform before persistence moves the widget.
+pub(super) fn remember_spawn_layout(mut world: DeferredWorld, context: HookContext) {
+  let Some(transform) = world.get::<Transform>(context.entity) else {
+    return;
+  };
+  let layout = WidgetLayout::read(transform, world.get::<Node>(context.entity));
+  world
+    .commands()
+    .entity(context.entity)
+    .insert(SpawnLayout(layout));
+}
+
+fn reset_selected(
+  _trigger: Trigger<ResetSelected>,
+  mut selected: Resettable,
+  mut commands: Commands,
+) {
+  for (entity, spawn_layout, mut transform, mut node) in &mut selected {
+    spawn_layout.0.apply(&mut transform, node.as_deref_mut());
+    commands.trigger_targets(LayoutChanged::Reset, entity);
+  }
+}
+
+impl Constraints<'_, '_> {
+  /// Limits how far `entity`, which started the drag at `start`, can be
+  /// moved so it stays inside its bounds. Only the translation of
+  /// `transform` is ignored.
+  pub(super) fn clamp_offset(
+    &self,
+    entity: Entity,
+    draggable: &DraggableInterface,
+    start: Vec2,
+    transform: &Transform,
+    offset: Vec2,
+  ) -> Vec2 {
+    let Some(bounds) = draggable
+      .bounds
+      .and_then(|bounds| self.bounds_rect(entity, bounds))
+    else {
+      return offset;
+    };
+
+    let placed = transform.with_translation(start.extend(0.0));
+    let rect = match self.aabbs.get(entity) {
+      Ok(aabb) => placed_rect(aabb, |point| placed.transform_point(point)),
+      Err(_) => Rect::from_corners(start, start),
+    };
+
+    Vec2::new(
+      clamp_axis(
+        offset.x,
+        bounds.min.x - rect.min.x,
+        bounds.max.x - rect.max.x,
+      ),
+      clamp_axis(
+        offset.y,
+        bounds.min.y - rect.min.y,
+        bounds.max.y - rect.max.y,
+      ),
+    )
+  }
+
+  fn bounds_rect(&self, entity: Entity, bounds: DragBounds) -> Option<Rect> {
+    match bounds {
+      DragBounds::Window => self.cameras.view_rect(entity),
+      DragBounds::Rect(rect) => Some(rect),
+    }
+  }
+}
+
+/// Widgets bigger than their bounds are kept centred in them.
+fn clamp_axis(offset: f32, min: f32, max: f32) -> f32 {
+  if min > max {
+    (min + max) * 0.5
+  } else {
+    offset.clamp(min, max)
+  }
+}
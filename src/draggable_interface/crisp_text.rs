@@ -0,0 +1,176 @@
+use bevy::{prelude::*, text::LineHeight, transform::TransformSystem};
+
+/// Font sizes are rounded to this many pixels. Every size text is drawn at
+/// gets its own glyph atlas, which is never freed, so an animated scale such
+/// as a pulse would otherwise keep making new ones.
+const FONT_SIZE_STEP: f32 = 0.5;
+
+/// Keeps a `Text2d` sharp however much it, or anything it sits on, is
+/// scaled, such as a label on a zoomed in widget.
+///
+/// The scale is moved into the font size, and the text's own scale shrunk to
+/// match, so it is drawn at the size it appears rather than stretched. For
+/// non-uniform scales the text is drawn for the larger axis. The font and
+/// line height set on the text are kept, and anything that changes them or
+/// the text's scale later is picked up.
+#[derive(Component, Default)]
+pub struct CrispText {
+  font_size: f32,
+  line_height: LineHeight,
+  scale: Vec3,
+  /// What was last written over the text, `None` until it has been.
+  written: Option<Written>,
+}
+
+#[derive(Clone, Copy)]
+struct Written {
+  font_size: f32,
+  line_height: LineHeight,
+  scale: Vec3,
+}
+
+pub(super) fn plugin(app: &mut App) {
+  app.add_systems(
+    PostUpdate,
+    sharpen_text.before(TransformSystem::TransformPropagate),
+  );
+}
+
+fn sharpen_text(
+  mut texts: Query<(Entity, &mut CrispText, &mut TextFont), With<Text2d>>,
+  mut transforms: Query<(&mut Transform, Option<&ChildOf>)>,
+) {
+  for (entity, mut crisp, mut text_font) in &mut texts {
+    let inherited = inherited_scale(entity, &transforms);
+    let Ok((mut transform, _)) = transforms.get_mut(entity) else {
+      continue;
+    };
+
+    crisp.pick_up_changes(&text_font, &transform);
+    let scale = inherited * crisp.scale;
+    let factor = scale.x.abs().max(scale.y.abs());
+    // Nothing to draw, and nothing sensible to divide by.
+    if factor <= f32::EPSILON || crisp.font_size <= f32::EPSILON {
+      continue;
+    }
+
+    // The scale makes up whatever rounding the font size took off.
+    let font_size = round_font_size(crisp.font_size * factor);
+    let factor = font_size / crisp.font_size;
+    let written = Written {
+      font_size,
+      line_height: scale_line_height(crisp.line_height, factor),
+      scale: crisp.scale / factor,
+    };
+
+    if text_font.font_size != written.font_size ||
+      !same_line_height(text_font.line_height, written.line_height)
+    {
+      text_font.font_size = written.font_size;
+      text_font.line_height = written.line_height;
+    }
+    if transform.scale != written.scale {
+      transform.scale = written.scale;
+    }
+    crisp.written = Some(written);
+  }
+}
+
+fn round_font_size(font_size: f32) -> f32 {
+  ((font_size / FONT_SIZE_STEP).round() * FONT_SIZE_STEP).max(FONT_SIZE_STEP)
+}
+
+/// The combined scale of every ancestor of `entity`.
+fn inherited_scale(entity: Entity, transforms: &Query<(&mut Transform, Option<&ChildOf>)>) -> Vec3 {
+  let mut scale = Vec3::ONE;
+  let mut parent = transforms
+    .get(entity)
+    .ok()
+    .and_then(|(_, child_of)| child_of.map(ChildOf::parent));
+
+  while let Some(entity) = parent {
+    let Ok((transform, child_of)) = transforms.get(entity) else {
+      break;
+    };
+    scale *= transform.scale;
+    parent = child_of.map(ChildOf::parent);
+  }
+  scale
+}
+
+fn scale_line_height(line_height: LineHeight, factor: f32) -> LineHeight {
+  match line_height {
+    LineHeight::Px(px) => LineHeight::Px(px * factor),
+    // Already follows the font size.
+    LineHeight::RelativeToFont(_) => line_height,
+  }
+}
+
+/// `LineHeight` can't be compared directly.
+fn same_line_height(a: LineHeight, b: LineHeight) -> bool {
+  match (a, b) {
+    (LineHeight::Px(a), LineHeight::Px(b)) => a == b,
+    (LineHeight::RelativeToFont(a), LineHeight::RelativeToFont(b)) => a == b,
+    _ => false,
+  }
+}
+
+impl CrispText {
+  /// Takes anything that no longer matches what was last written as the
+  /// new unscaled value.
+  fn pick_up_changes(&mut self, text_font: &TextFont, transform: &Transform) {
+    let Some(written) = self.written else {
+      self.font_size = text_font.font_size;
+      self.line_height = text_font.line_height;
+      self.scale = transform.scale;
+      return;
+    };
+
+    if text_font.font_size != written.font_size {
+      self.font_size = text_font.font_size;
+    }
+    if !same_line_height(text_font.line_height, written.line_height) {
+      self.line_height = text_font.line_height;
+    }
+    if transform.scale != written.scale {
+      self.scale = transform.scale;
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashSet;
+
+  use super::*;
+
+  #[test]
+  fn pulsing_draws_few_font_sizes() {
+    let mut app = App::new();
+    app.add_plugins(plugin);
+    let text = app
+      .world_mut()
+      .spawn((
+        Text2d::new("00:59"),
+        TextFont::from_font_size(40.0),
+        CrispText::default(),
+      ))
+      .id();
+
+    let mut font_sizes = HashSet::new();
+    for frame in 0..1000 {
+      // Grows up to ten percent bigger, as an urgent clock does.
+      let pulse = 1.0 + 0.1 * (frame as f32 * 0.37).sin().abs();
+      app.world_mut().get_mut::<Transform>(text).unwrap().scale = Vec3::splat(pulse);
+      app.update();
+
+      let font_size = app.world().get::<TextFont>(text).unwrap().font_size;
+      let scale = app.world().get::<Transform>(text).unwrap().scale;
+      assert!((font_size * scale.x - 40.0 * pulse).abs() < 1e-3);
+      font_sizes.insert(font_size.to_bits());
+    }
+
+    // 40 to 44 pixels in half pixel steps.
+    assert!(font_sizes.len() <= 9, "{} font sizes", font_sizes.len());
+  }
+}
@@ -0,0 +1,238 @@
+use std::collections::{HashMap, VecDeque};
+
+use bevy::prelude::*;
+
+use super::{
+  constraints::Locked, mode::OverlayMode, DraggableInterface, LayoutChanged, WidgetLayout,
+};
+
+const DEFAULT_MAX_STEPS: usize = 100;
+/// Wheel ticks closer together than this are undone as a single zoom or
+/// rotation.
+const WHEEL_COLLAPSE_SECONDS: f32 = 0.5;
+
+const CONTROL_KEYS: [KeyCode; 2] = [KeyCode::ControlLeft, KeyCode::ControlRight];
+const SHIFT_KEYS: [KeyCode; 2] = [KeyCode::ShiftLeft, KeyCode::ShiftRight];
+
+/// Locked widgets are left where they are.
+#[derive(Event, Clone)]
+pub struct Undo;
+
+/// Locked widgets are left where they are.
+#[derive(Event, Clone)]
+pub struct Redo;
+
+type Undoable<'w, 's> = Query<
+  'w,
+  's,
+  (&'static mut Transform, Option<&'static mut Node>),
+  (With<DraggableInterface>, Without<Locked>),
+>;
+
+/// One undoable step, which may have moved several widgets at once.
+struct LayoutEdit {
+  kind: LayoutChanged,
+  changes: HashMap<Entity, (WidgetLayout, WidgetLayout)>,
+  time: f32,
+}
+
+#[derive(Resource)]
+pub struct LayoutHistory {
+  max_steps: usize,
+  undo: VecDeque<LayoutEdit>,
+  redo: Vec<LayoutEdit>,
+  /// The last layout history knows about for each draggable, which is what
+  /// an edit gets undone back to.
+  snapshots: HashMap<Entity, WidgetLayout>,
+  pending: Vec<(Entity, LayoutChanged)>,
+}
+
+pub(super) fn plugin(app: &mut App) {
+  app
+    .insert_resource(LayoutHistory::new(DEFAULT_MAX_STEPS))
+    .add_systems(
+      Update,
+      (
+        snapshot_new_draggables,
+        undo_redo_keys.run_if(in_state(OverlayMode::Edit)),
+      ),
+    )
+    .add_systems(Last, record_pending_edits)
+    .add_observer(edit_made)
+    .add_observer(forget_removed)
+    .add_observer(undo)
+    .add_observer(redo);
+}
+
+fn snapshot_new_draggables(
+  draggables: Query<(Entity, &Transform, Option<&Node>), Added<DraggableInterface>>,
+  mut history: ResMut<LayoutHistory>,
+) {
+  for (entity, transform, node) in &draggables {
+    history
+      .snapshots
+      .insert(entity, WidgetLayout::read(transform, node));
+  }
+}
+
+fn forget_removed(
+  trigger: Trigger<OnRemove, DraggableInterface>,
+  mut history: ResMut<LayoutHistory>,
+) {
+  history.snapshots.remove(&trigger.target());
+}
+
+fn undo_redo_keys(keys: Res<ButtonInput<KeyCode>>, mut commands: Commands) {
+  if keys.any_pressed(CONTROL_KEYS) && keys.just_pressed(KeyCode::KeyZ) {
+    if keys.any_pressed(SHIFT_KEYS) {
+      commands.trigger(Redo);
+    } else {
+      commands.trigger(Undo);
+    }
+  }
+}
+
+fn edit_made(
+  trigger: Trigger<LayoutChanged>,
+  draggables: Query<(&Transform, Option<&Node>), With<DraggableInterface>>,
+  mut history: ResMut<LayoutHistory>,
+) {
+  let entity = trigger.target();
+  match trigger.event() {
+    LayoutChanged::Restored => {}
+    LayoutChanged::ProfileApplied => {
+      if let Ok((transform, node)) = draggables.get(entity) {
+        history
+          .snapshots
+          .insert(entity, WidgetLayout::read(transform, node));
+      }
+    }
+    kind => history.pending.push((entity, *kind)),
+  }
+}
+
+/// Everything changed in the same frame, like a group drag, becomes one edit.
+fn record_pending_edits(
+  draggables: Query<(&Transform, Option<&Node>), With<DraggableInterface>>,
+  time: Res<Time<Real>>,
+  mut history: ResMut<LayoutHistory>,
+) {
+  if history.pending.is_empty() {
+    return;
+  }
+
+  let pending = std::mem::take(&mut history.pending);
+  let now = time.elapsed_secs();
+  let kind = pending[0].1;
+
+  let mut changes = HashMap::new();
+  for (entity, _) in pending {
+    if let Ok((transform, node)) = draggables.get(entity) {
+      let layout = WidgetLayout::read(transform, node);
+      let before = history.snapshots.insert(entity, layout).unwrap_or(layout);
+      changes.entry(entity).or_insert((before, layout)).1 = layout;
+    }
+  }
+  if changes.is_empty() {
+    return;
+  }
+
+  history.redo.clear();
+
+  if let Some(last) = history.undo.back_mut() {
+    let collapses = matches!(kind, LayoutChanged::Zoomed | LayoutChanged::Rotated) &&
+      last.kind == kind &&
+      now - last.time <= WHEEL_COLLAPSE_SECONDS &&
+      last.changes.len() == changes.len() &&
+      changes
+        .keys()
+        .all(|entity| last.changes.contains_key(entity));
+
+    if collapses {
+      for (entity, (_, after)) in changes {
+        if let Some(change) = last.changes.get_mut(&entity) {
+          change.1 = after;
+        }
+      }
+      last.time = now;
+      return;
+    }
+  }
+
+  history.undo.push_back(LayoutEdit {
+    kind,
+    changes,
+    time: now,
+  });
+  while history.undo.len() > history.max_steps {
+    history.undo.pop_front();
+  }
+}
+
+fn undo(
+  _trigger: Trigger<Undo>,
+  mut draggables: Undoable,
+  mut history: ResMut<LayoutHistory>,
+  mut commands: Commands,
+) {
+  if let Some(edit) = history.undo.pop_back() {
+    history.apply(&edit, false, &mut draggables, &mut commands);
+    history.redo.push(edit);
+  }
+}
+
+fn redo(
+  _trigger: Trigger<Redo>,
+  mut draggables: Undoable,
+  mut history: ResMut<LayoutHistory>,
+  mut commands: Commands,
+) {
+  if let Some(edit) = history.redo.pop() {
+    history.apply(&edit, true, &mut draggables, &mut commands);
+    history.undo.push_back(edit);
+  }
+}
+
+impl LayoutHistory {
+  pub fn new(max_steps: usize) -> LayoutHistory {
+    LayoutHistory {
+      max_steps: max_steps.max(1),
+      undo: VecDeque::new(),
+      redo: Vec::new(),
+      snapshots: HashMap::new(),
+      pending: Vec::new(),
+    }
+  }
+
+  pub fn can_undo(&self) -> bool {
+    !self.undo.is_empty()
+  }
+
+  pub fn can_redo(&self) -> bool {
+    !self.redo.is_empty()
+  }
+
+  pub fn clear(&mut self) {
+    self.undo.clear();
+    self.redo.clear();
+  }
+
+  /// Puts every widget in `edit` back to how it was before, or after, the
+  /// edit. Widgets that have since been despawned or locked are skipped.
+  fn apply(
+    &mut self,
+    edit: &LayoutEdit,
+    forwards: bool,
+    draggables: &mut Undoable,
+    commands: &mut Commands,
+  ) {
+    for (entity, (before, after)) in &edit.changes {
+      let target = if forwards { *after } else { *before };
+      if let Ok((mut transform, mut node)) = draggables.get_mut(*entity) {
+        target.apply(&mut transform, node.as_deref_mut());
+        self.snapshots.insert(*entity, target);
+        commands.trigger_targets(LayoutChanged::Restored, *entity);
+      }
+    }
+  }
+}
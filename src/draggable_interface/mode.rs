@@ -0,0 +1,202 @@
+use bevy::{
+  color::palettes::css::{DARK_ORANGE, DEEP_SKY_BLUE, GRAY},
+  prelude::*,
+  render::primitives::Aabb,
+  window::WindowFocused,
+};
+
+use super::{constraints::Locked, world_rect, DraggableInterface, Hovered, Selected};
+
+/// Whether the overlay is being laid out or is live on stream.
+///
+/// In `Edit` draggables can be picked, moved and resized, and their bounds are
+/// drawn. In `Live` they ignore the pointer entirely, and windows stop taking
+/// hits, so clicks go straight through a transparent overlay to the desktop
+/// underneath.
+#[derive(States, Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OverlayMode {
+  #[default]
+  Edit,
+  /// A window that can't be clicked can't be given focus with the mouse
+  /// either, so keyboard shortcuts only reach it once it has been focused
+  /// some other way, such as alt-tab or the taskbar. Focusing it like that
+  /// goes back to `Edit` unless `LiveMode::edit_on_focus` is turned off.
+  Live,
+}
+
+/// How the overlay behaves in `OverlayMode::Live`.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct LiveMode {
+  /// Stops windows taking hits, so clicks go through to the desktop.
+  pub click_through: bool,
+  /// Goes back to `OverlayMode::Edit` when a window is focused again, which
+  /// is the dependable way out of a window that can't be clicked.
+  pub edit_on_focus: bool,
+}
+
+/// Triggered once the overlay has switched into a mode.
+#[derive(Event, Clone, Copy)]
+pub struct OverlayModeEntered(pub OverlayMode);
+
+/// Switches between edit and live mode.
+#[derive(Event, Clone)]
+pub struct ToggleOverlayMode;
+
+/// What an entity's `Pickable` was before live mode turned it off.
+#[derive(Component)]
+struct PickableBeforeLive(Option<Pickable>);
+
+type Outlined<'w, 's> = Query<
+  'w,
+  's,
+  (
+    &'static GlobalTransform,
+    Option<&'static Aabb>,
+    Has<Selected>,
+    Has<Locked>,
+  ),
+  (With<DraggableInterface>, Without<Node>),
+>;
+
+pub(super) fn plugin(app: &mut App) {
+  app
+    .init_state::<OverlayMode>()
+    .init_resource::<LiveMode>()
+    // Already added by `WindowPlugin`, but headless apps have no windows.
+    .add_event::<WindowFocused>()
+    .add_systems(OnEnter(OverlayMode::Edit), enter_edit_mode)
+    .add_systems(OnEnter(OverlayMode::Live), enter_live_mode)
+    .add_systems(PreUpdate, edit_on_focus)
+    .add_systems(Update, draw_bounds.run_if(in_state(OverlayMode::Edit)))
+    .add_observer(toggle_overlay_mode)
+    .add_observer(draggable_added);
+}
+
+fn toggle_overlay_mode(
+  _trigger: Trigger<ToggleOverlayMode>,
+  mode: Res<State<OverlayMode>>,
+  mut next_mode: ResMut<NextState<OverlayMode>>,
+) {
+  next_mode.set(match mode.get() {
+    OverlayMode::Edit => OverlayMode::Live,
+    OverlayMode::Live => OverlayMode::Edit,
+  });
+}
+
+/// Reads focus changes in every mode, and before the mode changes, so the
+/// window still being focused as it goes live doesn't bring it straight back.
+fn edit_on_focus(
+  mut focused: EventReader<WindowFocused>,
+  live_mode: Res<LiveMode>,
+  mode: Res<State<OverlayMode>>,
+  mut next_mode: ResMut<NextState<OverlayMode>>,
+) {
+  let refocused = focused.read().any(|event| event.focused);
+  if refocused && live_mode.edit_on_focus && *mode.get() == OverlayMode::Live {
+    next_mode.set(OverlayMode::Edit);
+  }
+}
+
+fn draggable_added(
+  trigger: Trigger<OnAdd, DraggableInterface>,
+  mode: Res<State<OverlayMode>>,
+  children: Query<&Children>,
+  pickables: Query<Option<&Pickable>>,
+  mut commands: Commands,
+) {
+  if *mode.get() == OverlayMode::Live {
+    make_unpickable(trigger.target(), &children, &pickables, &mut commands);
+  }
+}
+
+fn enter_live_mode(
+  draggables: Query<Entity, With<DraggableInterface>>,
+  children: Query<&Children>,
+  pickables: Query<Option<&Pickable>>,
+  live_mode: Res<LiveMode>,
+  mut windows: Query<&mut Window>,
+  mut commands: Commands,
+) {
+  set_hit_test(&mut windows, !live_mode.click_through);
+
+  for entity in &draggables {
+    commands
+      .entity(entity)
+      .remove::<Selected>()
+      .remove::<Hovered>();
+    make_unpickable(entity, &children, &pickables, &mut commands);
+  }
+
+  commands.trigger(OverlayModeEntered(OverlayMode::Live));
+}
+
+fn enter_edit_mode(
+  unpickable: Query<(Entity, &PickableBeforeLive)>,
+  mut windows: Query<&mut Window>,
+  mut commands: Commands,
+) {
+  set_hit_test(&mut windows, true);
+
+  for (entity, pickable_before) in &unpickable {
+    let mut entity = commands.entity(entity);
+    entity.remove::<PickableBeforeLive>();
+    match &pickable_before.0 {
+      Some(pickable) => entity.insert(pickable.clone()),
+      None => entity.remove::<Pickable>(),
+    };
+  }
+
+  commands.trigger(OverlayModeEntered(OverlayMode::Edit));
+}
+
+fn set_hit_test(windows: &mut Query<&mut Window>, hit_test: bool) {
+  for mut window in windows {
+    if window.cursor_options.hit_test != hit_test {
+      window.cursor_options.hit_test = hit_test;
+    }
+  }
+}
+
+/// Children are made unpickable too, otherwise their pointer events would
+/// still bubble up to the draggable.
+fn make_unpickable(
+  entity: Entity,
+  children: &Query<&Children>,
+  pickables: &Query<Option<&Pickable>>,
+  commands: &mut Commands,
+) {
+  for entity in std::iter::once(entity).chain(children.iter_descendants(entity)) {
+    if let Ok(pickable) = pickables.get(entity) {
+      commands
+        .entity(entity)
+        .insert((PickableBeforeLive(pickable.cloned()), Pickable::IGNORE));
+    }
+  }
+}
+
+fn draw_bounds(draggables: Outlined, mut gizmos: Gizmos) {
+  for (global_transform, aabb, selected, locked) in &draggables {
+    let rect = world_rect(global_transform, aabb);
+    let colour = if selected {
+      DEEP_SKY_BLUE
+    } else if locked {
+      DARK_ORANGE
+    } else {
+      GRAY
+    };
+    gizmos.rect_2d(
+      Isometry2d::from_translation(rect.center()),
+      rect.size(),
+      colour,
+    );
+  }
+}
+
+impl Default for LiveMode {
+  fn default() -> Self {
+    LiveMode {
+      click_through: true,
+      edit_on_focus: true,
+    }
+  }
+}
@@ -0,0 +1,139 @@
+use std::{
+  collections::BTreeMap,
+  fs,
+  path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::{DraggableInterface, LayoutChanged, WidgetLayout};
+
+const DEFAULT_LAYOUT_PATH: &str = "layout.ron";
+
+/// Saved positions of every draggable with an id, keyed by that id.
+///
+/// Entries for widgets that are not currently spawned are kept, so a widget
+/// that is removed and later spawned again still comes back where it was.
+#[derive(Resource)]
+pub struct LayoutStore {
+  path: PathBuf,
+  layouts: BTreeMap<String, WidgetLayout>,
+  dirty: bool,
+}
+
+pub(super) fn plugin(app: &mut App) {
+  app
+    .insert_resource(LayoutStore::load(DEFAULT_LAYOUT_PATH))
+    .add_systems(Last, write_layouts.run_if(layouts_dirty))
+    .add_observer(restore_layout)
+    .add_observer(record_layout);
+}
+
+fn restore_layout(
+  trigger: Trigger<OnAdd, DraggableInterface>,
+  mut draggables: Query<(&DraggableInterface, &mut Transform, Option<&mut Node>)>,
+  store: Res<LayoutStore>,
+) {
+  if let Ok((draggable, mut transform, mut node)) = draggables.get_mut(trigger.target()) {
+    if let Some(layout) = draggable.id().and_then(|id| store.get(id)) {
+      layout.apply(&mut transform, node.as_deref_mut());
+    }
+  }
+}
+
+fn record_layout(
+  trigger: Trigger<LayoutChanged>,
+  draggables: Query<(&DraggableInterface, &Transform, Option<&Node>)>,
+  mut store: ResMut<LayoutStore>,
+) {
+  if let Ok((draggable, transform, node)) = draggables.get(trigger.target()) {
+    if let Some(id) = draggable.id() {
+      store.set(id, WidgetLayout::read(transform, node));
+    }
+  }
+}
+
+fn layouts_dirty(store: Res<LayoutStore>) -> bool {
+  store.dirty
+}
+
+fn write_layouts(mut store: ResMut<LayoutStore>) {
+  if let Err(err) = store.save() {
+    warn!("Failed to save layout to {}: {}", store.path.display(), err);
+  }
+  store.dirty = false;
+}
+
+impl LayoutStore {
+  /// Loads layouts from `path`. A missing file gives an empty store, and a
+  /// file that can't be parsed is moved aside so it isn't overwritten.
+  pub fn load<P: AsRef<Path>>(path: P) -> LayoutStore {
+    let path = path.as_ref().to_path_buf();
+    LayoutStore {
+      layouts: load_ron(&path),
+      path,
+      dirty: false,
+    }
+  }
+
+  pub fn get(&self, id: &str) -> Option<&WidgetLayout> {
+    self.layouts.get(id)
+  }
+
+  pub fn set<S: Into<String>>(&mut self, id: S, layout: WidgetLayout) {
+    self.layouts.insert(id.into(), layout);
+    self.dirty = true;
+  }
+
+  pub fn remove(&mut self, id: &str) {
+    if self.layouts.remove(id).is_some() {
+      self.dirty = true;
+    }
+  }
+
+  pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+    save_ron(&self.path, &self.layouts)
+  }
+}
+
+/// Reads a RON file, giving the default value if it doesn't exist. A file
+/// that can't be parsed is moved aside so it isn't overwritten.
+pub(crate) fn load_ron<T: DeserializeOwned + Default>(path: &Path) -> T {
+  match fs::read_to_string(path) {
+    Ok(contents) => match ron::from_str(&contents) {
+      Ok(value) => value,
+      Err(err) => {
+        let backup = with_suffix(path, ".corrupt");
+        warn!(
+          "{} is corrupt ({}), moving it to {}",
+          path.display(),
+          err,
+          backup.display()
+        );
+        let _ = fs::rename(path, &backup);
+        T::default()
+      }
+    },
+    Err(_) => T::default(),
+  }
+}
+
+/// Writes to a temporary file first so a crash mid-write can't leave a half
+/// written file behind.
+pub(crate) fn save_ron<T: Serialize>(
+  path: &Path,
+  value: &T,
+) -> Result<(), Box<dyn std::error::Error>> {
+  let contents = ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())?;
+  let temp_path = with_suffix(path, ".tmp");
+  fs::write(&temp_path, contents)?;
+  fs::rename(&temp_path, path)?;
+  Ok(())
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+  let mut path = path.as_os_str().to_owned();
+  path.push(suffix);
+  PathBuf::from(path)
+}
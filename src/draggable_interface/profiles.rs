@@ -0,0 +1,331 @@
+use std::{
+  collections::BTreeMap,
+  path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{
+  constraints::Locked,
+  persistence::{load_ron, save_ron},
+  DraggableInterface, LayoutChanged, WidgetLayout,
+};
+
+const DEFAULT_PROFILES_PATH: &str = "layout_profiles.ron";
+
+const CONTROL_KEYS: [KeyCode; 2] = [KeyCode::ControlLeft, KeyCode::ControlRight];
+
+/// Named layouts, such as one per scene, that the overlay can switch
+/// between.
+///
+/// While a profile is active, edits to the layout are saved into it.
+#[derive(Resource, Serialize, Deserialize, Default)]
+pub struct LayoutProfiles {
+  #[serde(skip)]
+  path: PathBuf,
+  active: Option<String>,
+  profiles: BTreeMap<String, LayoutProfile>,
+  /// Pressing a key switches to its profile, holding Control as well saves
+  /// the current layout into it instead.
+  #[serde(skip)]
+  hotkeys: Vec<(KeyCode, SwitchLayoutProfile)>,
+  #[serde(skip)]
+  dirty: bool,
+}
+
+/// Where each widget is and whether it is shown, keyed by draggable id.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct LayoutProfile(pub BTreeMap<String, ProfileEntry>);
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct ProfileEntry {
+  pub layout: WidgetLayout,
+  pub visible: bool,
+}
+
+/// Moves every widget with an id to where the named profile has it. Locked
+/// widgets are left as they are.
+#[derive(Event, Clone)]
+pub struct SwitchLayoutProfile {
+  pub name: String,
+  /// Seconds to animate between the layouts for, or `None` to jump.
+  pub tween: Option<f32>,
+}
+
+/// Saves the current position and visibility of every widget with an id as
+/// the named profile, replacing it if it already exists.
+#[derive(Event, Clone)]
+pub struct SaveLayoutProfile(pub String);
+
+type SwitchableWidgets<'w, 's> = Query<
+  'w,
+  's,
+  (
+    Entity,
+    &'static DraggableInterface,
+    &'static mut Transform,
+    Option<&'static mut Node>,
+    &'static mut Visibility,
+  ),
+  Without<Locked>,
+>;
+
+/// Animates a widget from one layout to another.
+#[derive(Component)]
+struct LayoutTween {
+  from: WidgetLayout,
+  to: WidgetLayout,
+  visible: bool,
+  timer: Timer,
+}
+
+pub(super) fn plugin(app: &mut App) {
+  app
+    .insert_resource(LayoutProfiles::load(DEFAULT_PROFILES_PATH))
+    .add_systems(Update, (profile_hotkeys, tween_layouts))
+    .add_systems(Last, write_profiles.run_if(profiles_dirty))
+    .add_observer(switch_profile)
+    .add_observer(save_profile)
+    .add_observer(update_active_profile);
+}
+
+fn profile_hotkeys(
+  keys: Res<ButtonInput<KeyCode>>,
+  profiles: Res<LayoutProfiles>,
+  mut commands: Commands,
+) {
+  for (key, switch) in &profiles.hotkeys {
+    if !keys.just_pressed(*key) {
+      continue;
+    }
+
+    if keys.any_pressed(CONTROL_KEYS) {
+      commands.trigger(SaveLayoutProfile(switch.name.clone()));
+    } else {
+      commands.trigger(switch.clone());
+    }
+  }
+}
+
+fn save_profile(
+  trigger: Trigger<SaveLayoutProfile>,
+  draggables: Query<(&DraggableInterface, &Transform, Option<&Node>, &Visibility)>,
+  mut profiles: ResMut<LayoutProfiles>,
+) {
+  let mut profile = LayoutProfile::default();
+  for (draggable, transform, node, visibility) in &draggables {
+    if let Some(id) = draggable.id() {
+      profile.0.insert(
+        id.to_string(),
+        ProfileEntry {
+          layout: WidgetLayout::read(transform, node),
+          visible: *visibility != Visibility::Hidden,
+        },
+      );
+    }
+  }
+
+  let name = trigger.0.clone();
+  profiles.set(name.clone(), profile);
+  profiles.active = Some(name);
+}
+
+fn switch_profile(
+  trigger: Trigger<SwitchLayoutProfile>,
+  mut draggables: SwitchableWidgets,
+  mut profiles: ResMut<LayoutProfiles>,
+  mut commands: Commands,
+) {
+  let switch = trigger.event();
+  let Some(profile) = profiles.get(&switch.name).cloned() else {
+    warn!("There is no layout profile called {}", switch.name);
+    return;
+  };
+  profiles.active = Some(switch.name.clone());
+  profiles.dirty = true;
+
+  for (entity, draggable, mut transform, mut node, mut visibility) in &mut draggables {
+    let Some(entry) = draggable.id().and_then(|id| profile.0.get(id)) else {
+      continue;
+    };
+
+    match switch.tween.filter(|seconds| *seconds > 0.0) {
+      Some(seconds) => {
+        // Widgets being shown appear straight away and then move into place,
+        // ones being hidden only disappear once they arrive.
+        if entry.visible {
+          *visibility = Visibility::Inherited;
+        }
+        commands.entity(entity).insert(LayoutTween {
+          from: WidgetLayout::read(&transform, node.as_deref()),
+          to: entry.layout,
+          visible: entry.visible,
+          timer: Timer::from_seconds(seconds, TimerMode::Once),
+        });
+      }
+      None => {
+        entry.layout.apply(&mut transform, node.as_deref_mut());
+        *visibility = profile_visibility(entry.visible);
+        commands.entity(entity).remove::<LayoutTween>();
+        commands.trigger_targets(LayoutChanged::ProfileApplied, entity);
+      }
+    }
+  }
+}
+
+fn tween_layouts(
+  mut tweens: Query<(
+    Entity,
+    &mut LayoutTween,
+    &mut Transform,
+    Option<&mut Node>,
+    &mut Visibility,
+  )>,
+  time: Res<Time>,
+  mut commands: Commands,
+) {
+  for (entity, mut tween, mut transform, mut node, mut visibility) in &mut tweens {
+    tween.timer.tick(time.delta());
+
+    let t = EaseFunction::CubicInOut.sample_clamped(tween.timer.fraction());
+    lerp_layout(&tween.from, &tween.to, t).apply(&mut transform, node.as_deref_mut());
+
+    if tween.timer.finished() {
+      *visibility = profile_visibility(tween.visible);
+      commands.entity(entity).remove::<LayoutTween>();
+      commands.trigger_targets(LayoutChanged::ProfileApplied, entity);
+    }
+  }
+}
+
+/// Keeps the active profile in step with edits made while it is active.
+fn update_active_profile(
+  trigger: Trigger<LayoutChanged>,
+  draggables: Query<(&DraggableInterface, &Transform, Option<&Node>, &Visibility)>,
+  mut profiles: ResMut<LayoutProfiles>,
+) {
+  if *trigger.event() == LayoutChanged::ProfileApplied {
+    return;
+  }
+  let Ok((draggable, transform, node, visibility)) = draggables.get(trigger.target()) else {
+    return;
+  };
+  let Some(id) = draggable.id() else {
+    return;
+  };
+  let Some(active) = profiles.active.clone() else {
+    return;
+  };
+
+  if let Some(profile) = profiles.profiles.get_mut(&active) {
+    profile.0.insert(
+      id.to_string(),
+      ProfileEntry {
+        layout: WidgetLayout::read(transform, node),
+        visible: *visibility != Visibility::Hidden,
+      },
+    );
+    profiles.dirty = true;
+  }
+}
+
+fn profiles_dirty(profiles: Res<LayoutProfiles>) -> bool {
+  profiles.dirty
+}
+
+fn write_profiles(mut profiles: ResMut<LayoutProfiles>) {
+  if let Err(err) = save_ron(&profiles.path, &*profiles) {
+    warn!(
+      "Failed to save layout profiles to {}: {}",
+      profiles.path.display(),
+      err
+    );
+  }
+  profiles.dirty = false;
+}
+
+fn profile_visibility(visible: bool) -> Visibility {
+  if visible {
+    Visibility::Inherited
+  } else {
+    Visibility::Hidden
+  }
+}
+
+fn lerp_layout(from: &WidgetLayout, to: &WidgetLayout, t: f32) -> WidgetLayout {
+  WidgetLayout {
+    translation: from.translation.lerp(to.translation, t),
+    scale: from.scale.lerp(to.scale, t),
+    rotation: from.rotation.slerp(to.rotation, t),
+    left: lerp_val(from.left, to.left, t),
+    top: lerp_val(from.top, to.top, t),
+  }
+}
+
+/// Offsets in different units can't be blended, so they jump at the end.
+fn lerp_val(from: Option<Val>, to: Option<Val>, t: f32) -> Option<Val> {
+  match (from, to) {
+    (Some(Val::Px(from)), Some(Val::Px(to))) => Some(Val::Px(from.lerp(to, t))),
+    (Some(Val::Percent(from)), Some(Val::Percent(to))) => Some(Val::Percent(from.lerp(to, t))),
+    _ if t < 1.0 => from,
+    _ => to,
+  }
+}
+
+impl LayoutProfiles {
+  pub fn load<P: AsRef<Path>>(path: P) -> LayoutProfiles {
+    let path = path.as_ref().to_path_buf();
+    LayoutProfiles {
+      path: path.clone(),
+      ..load_ron(&path)
+    }
+  }
+
+  pub fn active(&self) -> Option<&str> {
+    self.active.as_deref()
+  }
+
+  pub fn get(&self, name: &str) -> Option<&LayoutProfile> {
+    self.profiles.get(name)
+  }
+
+  pub fn names(&self) -> impl Iterator<Item = &str> {
+    self.profiles.keys().map(String::as_str)
+  }
+
+  pub fn set<S: Into<String>>(&mut self, name: S, profile: LayoutProfile) {
+    self.profiles.insert(name.into(), profile);
+    self.dirty = true;
+  }
+
+  pub fn remove(&mut self, name: &str) {
+    if self.profiles.remove(name).is_some() {
+      if self.active.as_deref() == Some(name) {
+        self.active = None;
+      }
+      self.dirty = true;
+    }
+  }
+
+  /// Switches to the profile when `key` is pressed, or saves into it when
+  /// Control is held too.
+  pub fn bind(&mut self, key: KeyCode, switch: SwitchLayoutProfile) {
+    self.hotkeys.retain(|(bound, _)| *bound != key);
+    self.hotkeys.push((key, switch));
+  }
+}
+
+impl SwitchLayoutProfile {
+  pub fn new<S: Into<String>>(name: S) -> SwitchLayoutProfile {
+    SwitchLayoutProfile {
+      name: name.into(),
+      tween: None,
+    }
+  }
+
+  pub fn with_tween(mut self, seconds: f32) -> SwitchLayoutProfile {
+    self.tween = Some(seconds);
+    self
+  }
+}
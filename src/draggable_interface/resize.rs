@@ -0,0 +1,369 @@
+use bevy::{
+  color::palettes::css::WHITE,
+  prelude::*,
+  render::{primitives::Aabb, view::RenderLayers},
+};
+
+use super::{
+  camera::DragCameras, constraints::Locked, DraggableInterface, LayoutChanged, Selected,
+};
+
+const HANDLE_SIZE: f32 = 8.0;
+const HANDLE_Z_OFFSET: f32 = 10.0;
+
+/// Where on the widget's bounds each handle sits, -1 and 1 being the edges.
+const HANDLE_ANCHORS: [Vec2; 8] = [
+  Vec2::new(-1.0, 1.0),
+  Vec2::new(0.0, 1.0),
+  Vec2::new(1.0, 1.0),
+  Vec2::new(1.0, 0.0),
+  Vec2::new(1.0, -1.0),
+  Vec2::new(0.0, -1.0),
+  Vec2::new(-1.0, -1.0),
+  Vec2::new(-1.0, 0.0),
+];
+
+#[derive(Component)]
+struct ResizeHandle {
+  target: Entity,
+  anchor: Vec2,
+  start_scale: Vec3,
+  start_translation: Vec3,
+  hovered: bool,
+  dragging: bool,
+}
+
+#[derive(Component)]
+struct HasResizeHandles;
+
+#[derive(Resource)]
+struct ResizeHandleAssets {
+  mesh: Handle<Mesh>,
+  material: Handle<ColorMaterial>,
+}
+
+/// Draggables that can be resized but have no handles yet, along with the
+/// layers their handles are drawn on.
+type NeedsHandles<'w, 's> = Query<
+  'w,
+  's,
+  Option<&'static RenderLayers>,
+  (
+    With<DraggableInterface>,
+    With<Aabb>,
+    Without<HasResizeHandles>,
+    Without<Locked>,
+  ),
+>;
+
+type Resizable<'w, 's> = Query<
+  'w,
+  's,
+  (
+    &'static DraggableInterface,
+    &'static Aabb,
+    &'static mut Transform,
+  ),
+  (Without<ResizeHandle>, Without<Locked>),
+>;
+
+pub(super) fn plugin(app: &mut App) {
+  app
+    .add_systems(Startup, setup_handle_assets)
+    .add_systems(Update, (despawn_unused_handles, position_handles).chain())
+    .add_observer(spawn_handles);
+}
+
+fn setup_handle_assets(
+  mut meshes: ResMut<Assets<Mesh>>,
+  mut materials: ResMut<Assets<ColorMaterial>>,
+  mut commands: Commands,
+) {
+  commands.insert_resource(ResizeHandleAssets {
+    mesh: meshes.add(Rectangle::new(HANDLE_SIZE, HANDLE_SIZE)),
+    material: materials.add(ColorMaterial::from_color(WHITE)),
+  });
+}
+
+fn spawn_handles(
+  trigger: Trigger<OnAdd, Selected>,
+  draggables: NeedsHandles,
+  handle_assets: Option<Res<ResizeHandleAssets>>,
+  mut commands: Commands,
+) {
+  let Some(handle_assets) = handle_assets else {
+    return;
+  };
+  let Ok(layers) = draggables.get(trigger.target()) else {
+    return;
+  };
+
+  commands.entity(trigger.target()).insert(HasResizeHandles);
+  for anchor in HANDLE_ANCHORS {
+    let mut handle = commands.spawn((
+      Mesh2d(handle_assets.mesh.clone()),
+      MeshMaterial2d(handle_assets.material.clone()),
+      Transform::default(),
+      ResizeHandle {
+        target: trigger.target(),
+        anchor,
+        start_scale: Vec3::ONE,
+        start_translation: Vec3::ZERO,
+        hovered: false,
+        dragging: false,
+      },
+    ));
+    // Drawn by the same camera as the widget.
+    if let Some(layers) = layers {
+      handle.insert(layers.clone());
+    }
+    handle
+      .observe(set_handle_hovered_on::<Pointer<Over>>(true))
+      .observe(set_handle_hovered_on::<Pointer<Out>>(false))
+      .observe(handle_drag_started)
+      .observe(handle_dragged)
+      .observe(handle_drag_ended);
+  }
+}
+
+/// Handles stay around while their widget is selected, or while one of them
+/// is being used, as moving onto a handle unselects the widget underneath.
+fn despawn_unused_handles(
+  handles: Query<(Entity, &ResizeHandle)>,
+  draggables: Query<Has<Selected>, With<HasResizeHandles>>,
+  mut commands: Commands,
+) {
+  for (entity, handle) in &handles {
+    let Ok(selected) = draggables.get(handle.target) else {
+      commands.entity(entity).despawn();
+      continue;
+    };
+    let in_use = handles
+      .iter()
+      .any(|(_, other)| other.target == handle.target && (other.hovered || other.dragging));
+
+    if !selected && !in_use {
+      commands.entity(entity).despawn();
+      commands.entity(handle.target).remove::<HasResizeHandles>();
+    }
+  }
+}
+
+fn position_handles(
+  mut handles: Query<(&ResizeHandle, &mut Transform)>,
+  draggables: Query<(&GlobalTransform, &Aabb)>,
+) {
+  for (handle, mut transform) in &mut handles {
+    if let Ok((global_transform, aabb)) = draggables.get(handle.target) {
+      let corner =
+        Vec3::from(aabb.center) + Vec3::from(aabb.half_extents) * handle.anchor.extend(0.0);
+      transform.translation = global_transform.transform_point(corner);
+      transform.translation.z += HANDLE_Z_OFFSET;
+    }
+  }
+}
+
+fn set_handle_hovered_on<E>(hovered: bool) -> impl Fn(Trigger<E>, Query<&mut ResizeHandle>) {
+  move |trigger, mut handles| {
+    if let Ok(mut handle) = handles.get_mut(trigger.target()) {
+      handle.hovered = hovered;
+    }
+  }
+}
+
+fn handle_drag_started(
+  trigger: Trigger<Pointer<DragStart>>,
+  mut handles: Query<&mut ResizeHandle>,
+  transforms: Query<&Transform, Without<ResizeHandle>>,
+) {
+  if let Ok(mut handle) = handles.get_mut(trigger.target()) {
+    if let Ok(transform) = transforms.get(handle.target) {
+      handle.start_scale = transform.scale;
+      handle.start_translation = transform.translation;
+      handle.dragging = true;
+    }
+  }
+}
+
+/// Scales the widget so the dragged handle follows the pointer while the
+/// opposite edge or corner stays where it is. Works in the widget's own
+/// rotated frame, so rotated widgets are resized along their own edges.
+fn handle_dragged(
+  trigger: Trigger<Pointer<Drag>>,
+  handles: Query<&ResizeHandle>,
+  mut draggables: Resizable,
+  cameras: DragCameras,
+) {
+  let Ok(handle) = handles.get(trigger.target()) else {
+    return;
+  };
+  let Ok((draggable, aabb, mut transform)) = draggables.get_mut(handle.target) else {
+    return;
+  };
+
+  let center = aabb.center.truncate();
+  let half_extents = aabb.half_extents.truncate().max(Vec2::splat(f32::EPSILON));
+  let start_scale = handle.start_scale.truncate();
+  let rotation = transform.rotation;
+  let pointer = trigger.pointer_location.position;
+  // Through the widget rather than the handle, which has no parent, so the
+  // delta is in the same space as the widget's transform.
+  let delta = cameras.drag_delta(handle.target, pointer - trigger.distance, pointer);
+  let delta = (rotation.inverse() * delta).truncate();
+
+  let start_size = half_extents * 2.0 * start_scale;
+  let new_size = start_size + handle.anchor * delta;
+  let mut scale = Vec2::select(
+    handle.anchor.cmpeq(Vec2::ZERO),
+    start_scale,
+    new_size / (half_extents * 2.0),
+  );
+
+  let is_corner = handle.anchor.x != 0.0 && handle.anchor.y != 0.0;
+  if is_corner && draggable.lock_aspect_ratio {
+    let factor = (scale / start_scale).max_element();
+    scale = start_scale * factor;
+  }
+
+  let scale = draggable
+    .clamp_scale(scale.extend(handle.start_scale.z))
+    .truncate();
+
+  let fixed_edge = center - handle.anchor * half_extents;
+  let compensation = rotation * (fixed_edge * (start_scale - scale)).extend(0.0);
+  let translation = handle.start_translation.truncate() + compensation.truncate();
+
+  transform.scale = scale.extend(transform.scale.z);
+  transform.translation = translation.extend(transform.translation.z);
+}
+
+fn handle_drag_ended(
+  trigger: Trigger<Pointer<DragEnd>>,
+  mut handles: Query<&mut ResizeHandle>,
+  mut commands: Commands,
+) {
+  if let Ok(mut handle) = handles.get_mut(trigger.target()) {
+    handle.dragging = false;
+    commands.trigger_targets(LayoutChanged::Resized, handle.target);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use bevy::{
+    ecs::system::RunSystemOnce,
+    picking::pointer::{Location, PointerButton, PointerId},
+    render::camera::{camera_system, NormalizedRenderTarget},
+  };
+
+  use super::*;
+  use crate::draggable_interface::camera::tests::{spawn_camera, world_with_window};
+
+  /// A 20x20 widget at the origin of its parent.
+  fn spawn_widget(world: &mut World, extra: impl Bundle) -> Entity {
+    world
+      .spawn((
+        DraggableInterface::new(),
+        Aabb::from_min_max(Vec3::new(-10.0, -10.0, 0.0), Vec3::new(10.0, 10.0, 0.0)),
+        Transform::default(),
+        GlobalTransform::default(),
+        extra,
+      ))
+      .id()
+  }
+
+  /// Drags the handle on `widget`'s right edge `distance` pixels to the right
+  /// and returns the widget's transform.
+  fn drag_right_edge(world: &mut World, widget: Entity, distance: f32) -> Transform {
+    world.run_system_once(camera_system).unwrap();
+    let handle = world
+      .spawn(ResizeHandle {
+        target: widget,
+        anchor: Vec2::X,
+        start_scale: Vec3::ONE,
+        start_translation: Vec3::ZERO,
+        hovered: false,
+        dragging: true,
+      })
+      .observe(handle_dragged)
+      .id();
+
+    let distance = Vec2::new(distance, 0.0);
+    let location = Location {
+      target: NormalizedRenderTarget::Image(Handle::<Image>::default().into()),
+      position: Vec2::new(400.0, 300.0) + distance,
+    };
+    let drag = Drag {
+      button: PointerButton::Primary,
+      distance,
+      delta: distance,
+    };
+    world.trigger_targets(
+      Pointer::new(PointerId::Mouse, location, handle, drag),
+      handle,
+    );
+    world.flush();
+    *world.get::<Transform>(widget).unwrap()
+  }
+
+  fn assert_near(value: Vec3, expected: Vec3) {
+    assert!(
+      value.abs_diff_eq(expected, 1e-3),
+      "{} is not {}",
+      value,
+      expected
+    );
+  }
+
+  #[test]
+  fn zoomed_camera_on_another_layer() {
+    let mut world = world_with_window();
+    spawn_camera(&mut world, 1.0, Vec3::ZERO, RenderLayers::layer(0));
+    // Zoomed in twice over, so the edge moves half as far as the pointer.
+    spawn_camera(&mut world, 0.5, Vec3::ZERO, RenderLayers::layer(1));
+    let widget = spawn_widget(&mut world, RenderLayers::layer(1));
+
+    let transform = drag_right_edge(&mut world, widget, 20.0);
+    assert_near(transform.scale, Vec3::new(1.5, 1.0, 1.0));
+    // The left edge stays where it was.
+    assert_near(transform.translation, Vec3::new(5.0, 0.0, 0.0));
+  }
+
+  #[test]
+  fn parented_widget_resizes_in_parent_space() {
+    let mut world = world_with_window();
+    spawn_camera(&mut world, 1.0, Vec3::ZERO, RenderLayers::default());
+    let parent_transform = Transform::from_scale(Vec3::splat(2.0));
+    let parent = world
+      .spawn((parent_transform, GlobalTransform::from(parent_transform)))
+      .id();
+    let widget = spawn_widget(&mut world, ChildOf(parent));
+
+    // 20 pixels on screen is 10 in the parent, which is scaled up twice.
+    let transform = drag_right_edge(&mut world, widget, 20.0);
+    assert_near(transform.scale, Vec3::new(1.5, 1.0, 1.0));
+    assert_near(transform.translation, Vec3::new(5.0, 0.0, 0.0));
+  }
+
+  #[test]
+  fn handles_share_the_widget_layers() {
+    let mut app = App::new();
+    app.add_observer(spawn_handles);
+    app.world_mut().insert_resource(ResizeHandleAssets {
+      mesh: Handle::default(),
+      material: Handle::default(),
+    });
+    let widget = spawn_widget(app.world_mut(), RenderLayers::layer(3));
+    app.world_mut().entity_mut(widget).insert(Selected);
+    app.world_mut().flush();
+
+    let mut handles = app
+      .world_mut()
+      .query_filtered::<Option<&RenderLayers>, With<ResizeHandle>>();
+    let layers = handles.iter(app.world()).collect::<Vec<_>>();
+    assert_eq!(layers.len(), HANDLE_ANCHORS.len());
+    assert!(layers
+      .iter()
+      .all(|layers| *layers == Some(&RenderLayers::layer(3))));
+  }
+}
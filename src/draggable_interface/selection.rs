@@ -0,0 +1,271 @@
+use bevy::{color::palettes::css::DEEP_SKY_BLUE, prelude::*, render::primitives::Aabb};
+
+use super::{
+  constraints::Locked, mode::OverlayMode, world_rect, DraggableInterface, Hovered, LayoutChanged,
+  Selected, WidgetBounds,
+};
+
+const ADD_TO_SELECTION_KEYS: [KeyCode; 2] = [KeyCode::ShiftLeft, KeyCode::ShiftRight];
+
+/// Lines up the edges or centres of every selected widget.
+#[derive(Event, Clone, Copy)]
+pub enum AlignSelected {
+  Left,
+  Right,
+  Top,
+  Bottom,
+  HorizontalCenter,
+  VerticalCenter,
+}
+
+/// Spreads the selected widgets out so the gaps between them are equal.
+#[derive(Event, Clone, Copy)]
+pub enum DistributeSelected {
+  Horizontally,
+  Vertically,
+}
+
+/// The rubber band box being dragged out over empty space, in world space.
+#[derive(Resource, Default)]
+struct Marquee(Option<Rect>);
+
+/// Selected 2D widgets that can be lined up or spread out.
+type Arrangeable<'w, 's> = Query<
+  'w,
+  's,
+  (
+    Entity,
+    &'static GlobalTransform,
+    Option<&'static Aabb>,
+    &'static mut Transform,
+  ),
+  (With<Selected>, Without<Node>, Without<Locked>),
+>;
+
+pub(super) fn plugin(app: &mut App) {
+  app
+    .init_resource::<Marquee>()
+    .add_systems(Update, draw_marquee)
+    .add_observer(draggable_added)
+    .add_observer(marquee_started)
+    .add_observer(marquee_dragged)
+    .add_observer(marquee_ended)
+    .add_observer(clicked_empty_space)
+    .add_observer(align_selected)
+    .add_observer(distribute_selected);
+}
+
+fn draggable_added(trigger: Trigger<OnAdd, DraggableInterface>, mut commands: Commands) {
+  commands
+    .entity(trigger.target())
+    .observe(select_on_press)
+    .observe(set_hovered_on::<Pointer<Over>>(true))
+    .observe(set_hovered_on::<Pointer<Out>>(false));
+}
+
+fn set_hovered_on<E>(hovered: bool) -> impl Fn(Trigger<E>, Commands) {
+  move |trigger, mut commands| {
+    if hovered {
+      commands.entity(trigger.target()).insert(Hovered);
+    } else {
+      commands.entity(trigger.target()).remove::<Hovered>();
+    }
+  }
+}
+
+/// Shift-click toggles the widget in the selection. A plain click selects
+/// only this widget, unless it is already part of the selection so the
+/// whole group can be dragged.
+fn select_on_press(
+  trigger: Trigger<Pointer<Pressed>>,
+  keys: Res<ButtonInput<KeyCode>>,
+  selected: Query<Entity, With<Selected>>,
+  mut commands: Commands,
+) {
+  let entity = trigger.target();
+  let is_selected = selected.contains(entity);
+
+  if keys.any_pressed(ADD_TO_SELECTION_KEYS) {
+    if is_selected {
+      commands.entity(entity).remove::<Selected>();
+    } else {
+      commands.entity(entity).insert(Selected);
+    }
+  } else if !is_selected {
+    for other in &selected {
+      commands.entity(other).remove::<Selected>();
+    }
+    commands.entity(entity).insert(Selected);
+  }
+}
+
+fn clicked_empty_space(
+  trigger: Trigger<Pointer<Click>>,
+  windows: Query<(), With<Window>>,
+  keys: Res<ButtonInput<KeyCode>>,
+  selected: Query<Entity, With<Selected>>,
+  mut commands: Commands,
+) {
+  let started_on_window = trigger.event().target == trigger.target();
+  if !started_on_window || !windows.contains(trigger.target()) {
+    return;
+  }
+
+  if !keys.any_pressed(ADD_TO_SELECTION_KEYS) {
+    for entity in &selected {
+      commands.entity(entity).remove::<Selected>();
+    }
+  }
+}
+
+fn marquee_started(
+  trigger: Trigger<Pointer<DragStart>>,
+  windows: Query<(), With<Window>>,
+  camera: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+  mode: Res<State<OverlayMode>>,
+  mut marquee: ResMut<Marquee>,
+) {
+  if *mode.get() != OverlayMode::Edit {
+    return;
+  }
+  if trigger.event().target != trigger.target() || !windows.contains(trigger.target()) {
+    return;
+  }
+
+  if let Ok((camera, camera_transform)) = camera.single() {
+    if let Ok(position) =
+      camera.viewport_to_world_2d(camera_transform, trigger.pointer_location.position)
+    {
+      marquee.0 = Some(Rect::from_corners(position, position));
+    }
+  }
+}
+
+fn marquee_dragged(
+  trigger: Trigger<Pointer<Drag>>,
+  camera: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+  mut marquee: ResMut<Marquee>,
+) {
+  if marquee.0.is_none() {
+    return;
+  }
+
+  if let Ok((camera, camera_transform)) = camera.single() {
+    let start = camera.viewport_to_world_2d(
+      camera_transform,
+      trigger.pointer_location.position - trigger.distance,
+    );
+    let end = camera.viewport_to_world_2d(camera_transform, trigger.pointer_location.position);
+    if let (Ok(start), Ok(end)) = (start, end) {
+      marquee.0 = Some(Rect::from_corners(start, end));
+    }
+  }
+}
+
+fn marquee_ended(
+  _trigger: Trigger<Pointer<DragEnd>>,
+  keys: Res<ButtonInput<KeyCode>>,
+  draggables: WidgetBounds,
+  mut marquee: ResMut<Marquee>,
+  mut commands: Commands,
+) {
+  let Some(rect) = marquee.0.take() else {
+    return;
+  };
+
+  let add_to_selection = keys.any_pressed(ADD_TO_SELECTION_KEYS);
+  for (entity, global_transform, aabb, is_selected) in &draggables {
+    let bounds = world_rect(global_transform, aabb);
+    // Widgets without an `Aabb` have no size, so only their position counts.
+    let inside = if bounds.is_empty() {
+      rect.contains(bounds.center())
+    } else {
+      !rect.intersect(bounds).is_empty()
+    };
+    if inside {
+      commands.entity(entity).insert(Selected);
+    } else if is_selected && !add_to_selection {
+      commands.entity(entity).remove::<Selected>();
+    }
+  }
+}
+
+fn draw_marquee(marquee: Res<Marquee>, mut gizmos: Gizmos) {
+  if let Some(rect) = marquee.0 {
+    gizmos.rect_2d(
+      Isometry2d::from_translation(rect.center()),
+      rect.size(),
+      DEEP_SKY_BLUE,
+    );
+  }
+}
+
+fn align_selected(
+  trigger: Trigger<AlignSelected>,
+  mut selected: Arrangeable,
+  mut commands: Commands,
+) {
+  let bounds = selected
+    .iter()
+    .map(|(_, global_transform, aabb, _)| world_rect(global_transform, aabb))
+    .reduce(|a, b| a.union(b));
+  let Some(bounds) = bounds else {
+    return;
+  };
+
+  for (entity, global_transform, aabb, mut transform) in &mut selected {
+    let rect = world_rect(global_transform, aabb);
+    let offset = match *trigger.event() {
+      AlignSelected::Left => Vec2::new(bounds.min.x - rect.min.x, 0.0),
+      AlignSelected::Right => Vec2::new(bounds.max.x - rect.max.x, 0.0),
+      AlignSelected::Top => Vec2::new(0.0, bounds.max.y - rect.max.y),
+      AlignSelected::Bottom => Vec2::new(0.0, bounds.min.y - rect.min.y),
+      AlignSelected::HorizontalCenter => Vec2::new(bounds.center().x - rect.center().x, 0.0),
+      AlignSelected::VerticalCenter => Vec2::new(0.0, bounds.center().y - rect.center().y),
+    };
+
+    transform.translation += offset.extend(0.0);
+    commands.trigger_targets(LayoutChanged::Moved, entity);
+  }
+}
+
+fn distribute_selected(
+  trigger: Trigger<DistributeSelected>,
+  mut selected: Arrangeable,
+  mut commands: Commands,
+) {
+  let axis = match trigger.event() {
+    DistributeSelected::Horizontally => Vec2::X,
+    DistributeSelected::Vertically => Vec2::Y,
+  };
+
+  let mut rects = selected
+    .iter()
+    .map(|(entity, global_transform, aabb, _)| (entity, world_rect(global_transform, aabb)))
+    .collect::<Vec<_>>();
+  if rects.len() < 3 {
+    return;
+  }
+  rects.sort_by(|(_, a), (_, b)| a.min.dot(axis).total_cmp(&b.min.dot(axis)));
+
+  let start = rects.first().map_or(0.0, |(_, rect)| rect.min.dot(axis));
+  let end = rects
+    .iter()
+    .map(|(_, rect)| rect.max.dot(axis))
+    .fold(f32::MIN, f32::max);
+  let total_size = rects
+    .iter()
+    .map(|(_, rect)| rect.size().dot(axis))
+    .sum::<f32>();
+  let gap = (end - start - total_size) / (rects.len() - 1) as f32;
+
+  let mut next = start;
+  for (entity, rect) in rects {
+    if let Ok((_, _, _, mut transform)) = selected.get_mut(entity) {
+      let offset = next - rect.min.dot(axis);
+      transform.translation += (axis * offset).extend(0.0);
+      commands.trigger_targets(LayoutChanged::Moved, entity);
+    }
+    next += rect.size().dot(axis) + gap;
+  }
+}
@@ -0,0 +1,185 @@
+use bevy::{
+  color::palettes::css::MAGENTA, ecs::system::SystemParam, prelude::*, render::primitives::Aabb,
+};
+
+use super::{camera::DragCameras, placed_rect, world_rect, WidgetBounds};
+
+/// Controls how dragged widgets snap to the grid, to each other and to the
+/// window.
+#[derive(Resource)]
+pub struct Snapping {
+  /// Size of a grid cell, `None` turns grid snapping off.
+  pub grid_size: Option<f32>,
+  /// Snap to the edges and centres of other draggables and of the window, as
+  /// seen through the camera that renders the dragged widget.
+  pub guides: bool,
+  /// How close, in pixels, an edge has to be before it snaps to a guide.
+  pub threshold: f32,
+  /// Holding any of these keys while dragging turns snapping off.
+  pub disable_keys: Vec<KeyCode>,
+}
+
+#[derive(Clone, Copy)]
+enum Guide {
+  Vertical(f32),
+  Horizontal(f32),
+}
+
+#[derive(Resource, Default)]
+struct ActiveGuides {
+  guides: Vec<Guide>,
+  /// What the dragged widget's camera can see, which guides are drawn
+  /// across.
+  view: Option<Rect>,
+}
+
+/// Works out where a dragged entity should end up once snapping is applied.
+#[derive(SystemParam)]
+pub(super) struct Snapper<'w, 's> {
+  snapping: Res<'w, Snapping>,
+  keys: Res<'w, ButtonInput<KeyCode>>,
+  active_guides: ResMut<'w, ActiveGuides>,
+  bounds: WidgetBounds<'w, 's>,
+  cameras: DragCameras<'w, 's>,
+}
+
+pub(super) fn plugin(app: &mut App) {
+  app
+    .init_resource::<Snapping>()
+    .init_resource::<ActiveGuides>()
+    .add_systems(Update, draw_guides)
+    .add_observer(clear_guides);
+}
+
+fn clear_guides(_trigger: Trigger<Pointer<DragEnd>>, mut active_guides: ResMut<ActiveGuides>) {
+  active_guides.guides.clear();
+}
+
+fn draw_guides(active_guides: Res<ActiveGuides>, mut gizmos: Gizmos) {
+  let Some(view) = active_guides.view else {
+    return;
+  };
+
+  for guide in &active_guides.guides {
+    match *guide {
+      Guide::Vertical(x) => {
+        gizmos.line_2d(Vec2::new(x, view.min.y), Vec2::new(x, view.max.y), MAGENTA)
+      }
+      Guide::Horizontal(y) => {
+        gizmos.line_2d(Vec2::new(view.min.x, y), Vec2::new(view.max.x, y), MAGENTA)
+      }
+    }
+  }
+}
+
+impl Snapper<'_, '_> {
+  /// Snaps the `position` the user dragged `entity` to, first to the grid
+  /// and then to any guide that is within the threshold. Only the
+  /// translation of `transform` is ignored.
+  pub(super) fn snap(&mut self, entity: Entity, position: Vec2, transform: &Transform) -> Vec2 {
+    self.active_guides.guides.clear();
+
+    if self
+      .snapping
+      .disable_keys
+      .iter()
+      .any(|key| self.keys.pressed(*key))
+    {
+      return position;
+    }
+
+    let mut snapped = position;
+    if let Some(grid_size) = self.snapping.grid_size.filter(|size| *size > 0.0) {
+      snapped = (snapped / grid_size).round() * grid_size;
+    }
+
+    if !self.snapping.guides {
+      return snapped;
+    }
+
+    let placed = transform.with_translation(Vec3::ZERO);
+    let (offset, half_extents, dragging_selection) = match self.bounds.get(entity) {
+      Ok((_, _, Some(aabb), selected)) => {
+        let rect = placed_rect(aabb, |point| placed.transform_point(point));
+        (rect.center(), rect.half_size(), selected)
+      }
+      Ok((_, _, None, selected)) => (Vec2::ZERO, Vec2::ZERO, selected),
+      Err(_) => (Vec2::ZERO, Vec2::ZERO, false),
+    };
+
+    let mut vertical_lines = Vec::new();
+    let mut horizontal_lines = Vec::new();
+
+    let view = self.cameras.view_rect(entity);
+    if let Some(view) = view {
+      let center = view.center();
+      vertical_lines.extend([view.min.x, center.x, view.max.x]);
+      horizontal_lines.extend([view.min.y, center.y, view.max.y]);
+    }
+    self.active_guides.view = view;
+
+    for (other, global_transform, aabb, selected) in &self.bounds {
+      // The rest of a dragged selection moves along, so it can't be a guide.
+      if other == entity || (dragging_selection && selected) {
+        continue;
+      }
+
+      let rect = world_rect(global_transform, aabb);
+      let center = rect.center();
+      vertical_lines.extend([rect.min.x, center.x, rect.max.x]);
+      horizontal_lines.extend([rect.min.y, center.y, rect.max.y]);
+    }
+
+    let center = snapped + offset;
+    let threshold = self.snapping.threshold;
+
+    if let Some((line, delta)) = closest_line(
+      [
+        center.x - half_extents.x,
+        center.x,
+        center.x + half_extents.x,
+      ],
+      &vertical_lines,
+      threshold,
+    ) {
+      snapped.x += delta;
+      self.active_guides.guides.push(Guide::Vertical(line));
+    }
+
+    if let Some((line, delta)) = closest_line(
+      [
+        center.y - half_extents.y,
+        center.y,
+        center.y + half_extents.y,
+      ],
+      &horizontal_lines,
+      threshold,
+    ) {
+      snapped.y += delta;
+      self.active_guides.guides.push(Guide::Horizontal(line));
+    }
+
+    snapped
+  }
+}
+
+/// Finds the guide line closest to any of `edges`, returning the line and how
+/// far the edge has to move to sit on it.
+fn closest_line(edges: [f32; 3], lines: &[f32], threshold: f32) -> Option<(f32, f32)> {
+  edges
+    .iter()
+    .flat_map(|edge| lines.iter().map(move |line| (*line, line - edge)))
+    .filter(|(_, delta)| delta.abs() <= threshold)
+    .min_by(|(_, a), (_, b)| a.abs().total_cmp(&b.abs()))
+}
+
+impl Default for Snapping {
+  fn default() -> Self {
+    Snapping {
+      grid_size: None,
+      guides: true,
+      threshold: 8.0,
+      disable_keys: vec![KeyCode::AltLeft, KeyCode::AltRight],
+    }
+  }
+}
@@ -0,0 +1,78 @@
+use bevy::{ecs::system::SystemParam, prelude::*, window::PrimaryWindow};
+
+/// Moves UI nodes by their `left` and `top` offsets, keeping whichever unit
+/// each offset was already given in.
+#[derive(SystemParam)]
+pub(super) struct NodeMover<'w, 's> {
+  ui_scale: Res<'w, UiScale>,
+  parents: Query<'w, 's, &'static ChildOf>,
+  computed_nodes: Query<'w, 's, &'static ComputedNode>,
+  window: Query<'w, 's, &'static Window, With<PrimaryWindow>>,
+}
+
+impl NodeMover<'_, '_> {
+  /// Moves `node` from its offsets at the start of the drag by however far
+  /// the pointer has moved on screen.
+  pub(super) fn drag(&self, entity: Entity, node: &mut Node, start: Vec2, screen_delta: Vec2) {
+    let delta = screen_delta / self.ui_scale.0;
+    let viewport = self.viewport_size();
+    let container = self.container_size(entity).unwrap_or(viewport);
+
+    node.left = offset_by(node.left, start.x, delta.x, container.x, viewport);
+    node.top = offset_by(node.top, start.y, delta.y, container.y, viewport);
+  }
+
+  fn viewport_size(&self) -> Vec2 {
+    self
+      .window
+      .single()
+      .map_or(Vec2::ZERO, |window| window.size() / self.ui_scale.0)
+  }
+
+  /// The size percentages are relative to, which is the parent node's, or
+  /// `None` for root nodes.
+  fn container_size(&self, entity: Entity) -> Option<Vec2> {
+    let parent = self.parents.get(entity).ok()?.parent();
+    let computed_node = self.computed_nodes.get(parent).ok()?;
+    Some(computed_node.size() * computed_node.inverse_scale_factor())
+  }
+}
+
+/// The `left` and `top` offsets of a node in their own units, with `Auto`
+/// counting as zero.
+pub(super) fn node_offsets(node: &Node) -> Vec2 {
+  Vec2::new(val_amount(node.left), val_amount(node.top))
+}
+
+fn val_amount(val: Val) -> f32 {
+  match val {
+    Val::Auto => 0.0,
+    Val::Px(amount) |
+    Val::Percent(amount) |
+    Val::Vw(amount) |
+    Val::Vh(amount) |
+    Val::VMin(amount) |
+    Val::VMax(amount) => amount,
+  }
+}
+
+/// `start` moved by `delta` logical pixels, converted back into the unit of
+/// `val`. `Auto` offsets become pixels.
+fn offset_by(val: Val, start: f32, delta: f32, container: f32, viewport: Vec2) -> Val {
+  let percent_of = |size: f32| {
+    if size > 0.0 {
+      start + delta / size * 100.0
+    } else {
+      start
+    }
+  };
+
+  match val {
+    Val::Auto | Val::Px(_) => Val::Px(start + delta),
+    Val::Percent(_) => Val::Percent(percent_of(container)),
+    Val::Vw(_) => Val::Vw(percent_of(viewport.x)),
+    Val::Vh(_) => Val::Vh(percent_of(viewport.y)),
+    Val::VMin(_) => Val::VMin(percent_of(viewport.min_element())),
+    Val::VMax(_) => Val::VMax(percent_of(viewport.max_element())),
+  }
+}
@@ -1,29 +1,105 @@
 use std::time::Duration;
 
-use bevy::prelude::*;
+use bevy::{ecs::system::SystemId, prelude::*};
+
+mod transition;
+
+pub use transition::{ExpireTransition, TransitionKind};
 
 #[derive(Component)]
-pub struct Expire(pub Timer);
+pub struct Expire {
+  pub timer: Timer,
+  pub action: ExpireAction,
+  /// Plays as `Expire` is added.
+  pub enter: Option<ExpireTransition>,
+  /// Plays over the timer's last `seconds`, and plays backwards if time is
+  /// added during it.
+  pub exit: Option<ExpireTransition>,
+}
 
 #[derive(Component)]
 pub struct Expired;
 
+type TriggerOnExpire = Box<dyn Fn(&mut Commands, Entity) + Send + Sync>;
+
+/// What happens to an entity once its `Expire` timer runs out. `Expire` is
+/// removed whichever is used.
+#[derive(Default)]
+pub enum ExpireAction {
+  /// Inserts `Expired`, for the entity's own observers to deal with.
+  #[default]
+  Expired,
+  /// Despawns the entity along with its children.
+  Despawn,
+  Hide,
+  /// Made with `ExpireAction::remove`.
+  RemoveComponent(fn(&mut EntityCommands)),
+  /// Made with `ExpireAction::trigger`, triggers an event targeting the
+  /// entity.
+  Trigger(TriggerOnExpire),
+  /// Runs a one-shot system, given the entity that expired.
+  RunSystem(SystemId<In<Entity>>),
+}
+
 impl Expire {
   pub fn new(seconds: f32) -> Expire {
     let timer = Timer::new(Duration::from_secs_f32(seconds), TimerMode::Once);
-    Expire(timer)
+    Expire {
+      timer,
+      action: ExpireAction::default(),
+      enter: None,
+      exit: None,
+    }
+  }
+
+  pub fn with_action(mut self, action: ExpireAction) -> Expire {
+    self.action = action;
+    self
+  }
+
+  pub fn with_enter(mut self, transition: ExpireTransition) -> Expire {
+    self.enter = Some(transition);
+    self
+  }
+
+  pub fn with_exit(mut self, transition: ExpireTransition) -> Expire {
+    self.exit = Some(transition);
+    self
   }
 
+  /// Negative `seconds` take time off, expiring no sooner than the next tick.
   pub fn add_time(&mut self, seconds: f32) {
-    let mut time_left = self.0.duration().as_secs_f32() - self.0.elapsed_secs();
-    time_left += seconds;
-    self.0.set_duration(Duration::from_secs_f32(time_left));
-    self.0.reset();
+    let mut time_left = self.timer.duration().as_secs_f32() - self.timer.elapsed_secs();
+    time_left = (time_left + seconds).max(0.0);
+    self.timer.set_duration(Duration::from_secs_f32(time_left));
+    self.timer.reset();
+  }
+
+  pub fn subtract_time(&mut self, seconds: f32) {
+    self.add_time(-seconds.abs());
+  }
+}
+
+impl ExpireAction {
+  /// Removes `T` from the entity, such as a highlight that only lasts a
+  /// while.
+  pub fn remove<T: Bundle>() -> ExpireAction {
+    ExpireAction::RemoveComponent(|entity| {
+      entity.remove::<T>();
+    })
+  }
+
+  pub fn trigger<E: Event + Clone>(event: E) -> ExpireAction {
+    ExpireAction::Trigger(Box::new(move |commands, entity| {
+      commands.trigger_targets(event.clone(), entity);
+    }))
   }
 }
 
 pub(super) fn plugin(app: &mut App) {
-  app.add_systems(FixedUpdate, expire_entities);
+  app
+    .add_systems(FixedUpdate, expire_entities)
+    .add_plugins(transition::plugin);
 }
 
 fn expire_entities(
@@ -31,10 +107,66 @@ fn expire_entities(
   mut commands: Commands,
   time: Res<Time>,
 ) {
-  for (entity, mut timer) in &mut entities {
-    timer.0.tick(time.delta());
-    if timer.0.finished() {
-      commands.entity(entity).remove::<Expire>().insert(Expired);
+  for (entity, mut expire) in &mut entities {
+    expire.timer.tick(time.delta());
+    if !expire.timer.finished() {
+      continue;
     }
+
+    commands.entity(entity).remove::<Expire>();
+    match &expire.action {
+      ExpireAction::Expired => {
+        commands.entity(entity).insert(Expired);
+      }
+      ExpireAction::Despawn => {
+        commands.entity(entity).despawn();
+      }
+      ExpireAction::Hide => {
+        commands.entity(entity).insert(Visibility::Hidden);
+      }
+      ExpireAction::RemoveComponent(remove) => {
+        remove(&mut commands.entity(entity));
+      }
+      ExpireAction::Trigger(trigger) => {
+        trigger(&mut commands, entity);
+      }
+      ExpireAction::RunSystem(system) => {
+        commands.run_system_with(*system, entity);
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn subtracting_floors_at_zero() {
+    let mut expire = Expire::new(1.0);
+    expire.subtract_time(5.0);
+    assert_eq!(expire.timer.remaining_secs(), 0.0);
+
+    expire.timer.tick(Duration::ZERO);
+    assert!(expire.timer.finished());
+  }
+
+  #[test]
+  fn subtracting_ignores_sign() {
+    let mut expire = Expire::new(10.0);
+    expire.subtract_time(-3.0);
+    assert_eq!(expire.timer.remaining_secs(), 7.0);
+
+    expire.add_time(-20.0);
+    assert_eq!(expire.timer.remaining_secs(), 0.0);
+  }
+
+  #[test]
+  fn subtracting_keeps_elapsed_time() {
+    let mut expire = Expire::new(10.0);
+    expire.timer.tick(Duration::from_secs(4));
+    expire.subtract_time(2.0);
+    assert_eq!(expire.timer.remaining_secs(), 4.0);
+    assert!(!expire.timer.finished());
   }
 }
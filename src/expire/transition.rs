@@ -0,0 +1,439 @@
+use bevy::{
+  ecs::{system::SystemParam, world::OnDespawn},
+  prelude::*,
+};
+
+use super::Expire;
+
+/// Fades or shrinks an entity in as `Expire` is added, or out over its last
+/// few seconds. The entity is left how its transitions ended until it is
+/// hidden, despawned or given a new `Expire`.
+#[derive(Clone, Copy, Debug)]
+pub struct ExpireTransition {
+  pub kind: TransitionKind,
+  pub seconds: f32,
+  pub ease: EaseFunction,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransitionKind {
+  /// Fades the alpha of the entity's `Sprite`, `TextColor`, `BackgroundColor`
+  /// and `ColorMaterial`. Materials are assets, so every entity sharing one
+  /// fades with it.
+  Fade,
+  /// Scales the entity's `Transform` from, or down to, nothing.
+  Scale,
+}
+
+/// What the entity looked like before its transitions started. It outlives
+/// `Expire`, which leaves the entity faded or shrunk away, and is put back
+/// once the entity is hidden or despawned, or a new `Expire` starts.
+#[derive(Component)]
+struct TransitionState {
+  /// Seconds since `Expire` was added, which adding time doesn't reset.
+  age: f32,
+  look: Look,
+  /// The fade and scale `Expire` ended on, still to be applied.
+  ended: Option<(f32, f32)>,
+}
+
+#[derive(Clone, Copy, Default)]
+struct Look {
+  scale: Option<Vec3>,
+  sprite_alpha: Option<f32>,
+  text_alpha: Option<f32>,
+  background_alpha: Option<f32>,
+  material_alpha: Option<f32>,
+}
+
+/// Everything a transition can change.
+#[derive(SystemParam)]
+struct TransitionTargets<'w, 's> {
+  transforms: Query<'w, 's, &'static mut Transform>,
+  sprites: Query<'w, 's, &'static mut Sprite>,
+  text_colors: Query<'w, 's, &'static mut TextColor>,
+  backgrounds: Query<'w, 's, &'static mut BackgroundColor>,
+  materials: Query<'w, 's, &'static MeshMaterial2d<ColorMaterial>>,
+  material_assets: Option<ResMut<'w, Assets<ColorMaterial>>>,
+}
+
+pub(super) fn plugin(app: &mut App) {
+  app
+    .add_systems(Update, (animate_transitions, restore_hidden).chain())
+    .add_observer(start_transitions)
+    .add_observer(end_transitions)
+    .add_observer(restore_despawned);
+}
+
+/// A new `Expire` starts from how the entity looked before the last one, not
+/// how the last one left it.
+fn start_transitions(
+  trigger: Trigger<OnAdd, Expire>,
+  expires: Query<&Expire>,
+  states: Query<&TransitionState>,
+  mut targets: TransitionTargets,
+  mut commands: Commands,
+) {
+  let entity = trigger.target();
+  let Ok(expire) = expires.get(entity) else {
+    return;
+  };
+  let previous = states.get(entity).ok().map(|state| state.look);
+  if expire.enter.is_none() && expire.exit.is_none() {
+    if let Some(look) = previous {
+      targets.apply(entity, &look, 1.0, 1.0);
+      commands.entity(entity).remove::<TransitionState>();
+    }
+    return;
+  }
+
+  let state = TransitionState {
+    age: 0.0,
+    look: previous.unwrap_or_else(|| targets.read(entity)),
+    ended: None,
+  };
+  // Entering starts from nothing, so it mustn't be drawn as it is first.
+  let (fade, scale) = transition_factors(expire, state.age);
+  targets.apply(entity, &state.look, fade, scale);
+  commands.entity(entity).insert(state);
+}
+
+fn animate_transitions(
+  mut transitioning: Query<(Entity, Option<&Expire>, &mut TransitionState)>,
+  mut targets: TransitionTargets,
+  time: Res<Time>,
+) {
+  for (entity, expire, mut state) in &mut transitioning {
+    let (fade, scale) = match (expire, state.ended.take()) {
+      (Some(expire), _) => {
+        state.age += time.delta_secs();
+        transition_factors(expire, state.age)
+      }
+      (None, Some(ended)) => ended,
+      (None, None) => continue,
+    };
+    targets.apply(entity, &state.look, fade, scale);
+  }
+}
+
+/// Leaves the entity how its transitions ended, so a widget that outlives
+/// `Expire` doesn't pop back. It's applied on the next update rather than
+/// here, as a despawning entity is put back before `Expire` is removed.
+fn end_transitions(
+  trigger: Trigger<OnRemove, Expire>,
+  mut states: Query<(&Expire, &mut TransitionState)>,
+) {
+  if let Ok((expire, mut state)) = states.get_mut(trigger.target()) {
+    state.ended = Some(transition_factors(expire, state.age));
+  }
+}
+
+/// Hidden entities are put back how they looked, ready to be shown again.
+fn restore_hidden(
+  ended: Query<(Entity, &Visibility, &TransitionState), Without<Expire>>,
+  mut targets: TransitionTargets,
+  mut commands: Commands,
+) {
+  for (entity, visibility, state) in &ended {
+    if *visibility == Visibility::Hidden {
+      targets.apply(entity, &state.look, 1.0, 1.0);
+      commands.entity(entity).remove::<TransitionState>();
+    }
+  }
+}
+
+/// Materials are shared with other entities, so they are put back even when
+/// the entity is going.
+fn restore_despawned(
+  trigger: Trigger<OnDespawn, TransitionState>,
+  states: Query<&TransitionState>,
+  mut targets: TransitionTargets,
+) {
+  let entity = trigger.target();
+  if let Ok(state) = states.get(entity) {
+    targets.apply(entity, &state.look, 1.0, 1.0);
+  }
+}
+
+/// How far faded in and scaled up the entity should be, from zero for
+/// nothing to one for how it normally looks.
+fn transition_factors(expire: &Expire, age: f32) -> (f32, f32) {
+  let mut fade = 1.0;
+  let mut scale = 1.0;
+  let transitions = [
+    (expire.enter, age),
+    (expire.exit, expire.timer.remaining_secs()),
+  ];
+
+  for (transition, seconds) in transitions {
+    let Some(transition) = transition else {
+      continue;
+    };
+    let progress = if transition.seconds > 0.0 {
+      seconds / transition.seconds
+    } else {
+      1.0
+    };
+    let factor = transition.ease.sample_clamped(progress.clamp(0.0, 1.0));
+    match transition.kind {
+      TransitionKind::Fade => fade *= factor,
+      TransitionKind::Scale => scale *= factor,
+    }
+  }
+
+  (fade, scale)
+}
+
+impl TransitionTargets<'_, '_> {
+  fn read(&self, entity: Entity) -> Look {
+    Look {
+      scale: self
+        .transforms
+        .get(entity)
+        .ok()
+        .map(|transform| transform.scale),
+      sprite_alpha: self
+        .sprites
+        .get(entity)
+        .ok()
+        .map(|sprite| sprite.color.alpha()),
+      text_alpha: self
+        .text_colors
+        .get(entity)
+        .ok()
+        .map(|color| color.0.alpha()),
+      background_alpha: self
+        .backgrounds
+        .get(entity)
+        .ok()
+        .map(|color| color.0.alpha()),
+      material_alpha: self
+        .materials
+        .get(entity)
+        .ok()
+        .zip(self.material_assets.as_ref())
+        .and_then(|(material, assets)| assets.get(&material.0))
+        .map(|material| material.color.alpha()),
+    }
+  }
+
+  fn apply(&mut self, entity: Entity, look: &Look, fade: f32, scale: f32) {
+    if let (Some(base), Ok(mut transform)) = (look.scale, self.transforms.get_mut(entity)) {
+      transform.scale = base * scale;
+    }
+    if let (Some(alpha), Ok(mut sprite)) = (look.sprite_alpha, self.sprites.get_mut(entity)) {
+      sprite.color.set_alpha(alpha * fade);
+    }
+    if let (Some(alpha), Ok(mut color)) = (look.text_alpha, self.text_colors.get_mut(entity)) {
+      color.0.set_alpha(alpha * fade);
+    }
+    if let (Some(alpha), Ok(mut color)) = (look.background_alpha, self.backgrounds.get_mut(entity))
+    {
+      color.0.set_alpha(alpha * fade);
+    }
+    if let (Some(alpha), Ok(material)) = (look.material_alpha, self.materials.get(entity)) {
+      if let Some(material) = self
+        .material_assets
+        .as_mut()
+        .and_then(|assets| assets.get_mut(&material.0))
+      {
+        material.color.set_alpha(alpha * fade);
+      }
+    }
+  }
+}
+
+impl ExpireTransition {
+  pub fn fade(seconds: f32) -> ExpireTransition {
+    ExpireTransition {
+      kind: TransitionKind::Fade,
+      seconds,
+      ease: EaseFunction::CubicInOut,
+    }
+  }
+
+  pub fn scale(seconds: f32) -> ExpireTransition {
+    ExpireTransition {
+      kind: TransitionKind::Scale,
+      ..ExpireTransition::fade(seconds)
+    }
+  }
+
+  pub fn with_ease(mut self, ease: EaseFunction) -> ExpireTransition {
+    self.ease = ease;
+    self
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use bevy::time::TimeUpdateStrategy;
+
+  use super::*;
+  use crate::expire::ExpireAction;
+
+  /// Every update moves time on by this, with one fixed update each, so
+  /// after `n` updates a transition is `0.1 * (n - 1)` seconds in.
+  const STEP_SECONDS: f32 = 0.1;
+
+  fn app() -> App {
+    let mut app = App::new();
+    app
+      .add_plugins((MinimalPlugins, AssetPlugin::default()))
+      .init_asset::<ColorMaterial>()
+      .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+        STEP_SECONDS,
+      )))
+      .insert_resource(Time::<Fixed>::from_seconds(STEP_SECONDS as f64))
+      .add_plugins(crate::expire::plugin);
+    app
+  }
+
+  /// Expires after a second, transitioning in over its first 0.4 seconds and
+  /// out over its last.
+  fn expire(kind: TransitionKind) -> Expire {
+    let transition = ExpireTransition {
+      kind,
+      seconds: 0.4,
+      ease: EaseFunction::Linear,
+    };
+    Expire::new(1.0)
+      .with_enter(transition)
+      .with_exit(transition)
+  }
+
+  fn spawn(app: &mut App, expire: Expire) -> Entity {
+    app
+      .world_mut()
+      .spawn((
+        Transform::from_scale(Vec3::splat(2.0)),
+        Sprite::from_color(Color::srgba(1.0, 1.0, 1.0, 0.5), Vec2::ONE),
+        Visibility::Visible,
+        expire,
+      ))
+      .id()
+  }
+
+  fn update(app: &mut App, times: usize) {
+    for _ in 0..times {
+      app.update();
+    }
+  }
+
+  fn alpha(app: &App, entity: Entity) -> f32 {
+    app.world().get::<Sprite>(entity).unwrap().color.alpha()
+  }
+
+  fn scale(app: &App, entity: Entity) -> f32 {
+    app.world().get::<Transform>(entity).unwrap().scale.x
+  }
+
+  fn assert_near(value: f32, expected: f32) {
+    assert!(
+      (value - expected).abs() < 1e-3,
+      "{} is not {}",
+      value,
+      expected
+    );
+  }
+
+  /// Checks `look` at the start, middle and end of entering and exiting,
+  /// and once `Expire` has been removed.
+  fn check_windows(kind: TransitionKind, look: fn(&App, Entity) -> f32, base: f32) {
+    let mut app = app();
+    let entity = spawn(&mut app, expire(kind));
+    assert_near(look(&app, entity), 0.0);
+
+    update(&mut app, 1);
+    assert_near(look(&app, entity), 0.0);
+    update(&mut app, 2);
+    assert_near(look(&app, entity), base * 0.5);
+    update(&mut app, 2);
+    assert_near(look(&app, entity), base);
+
+    update(&mut app, 2);
+    assert_near(look(&app, entity), base);
+    update(&mut app, 2);
+    assert_near(look(&app, entity), base * 0.5);
+    update(&mut app, 2);
+    assert!(app.world().get::<Expire>(entity).is_none());
+    assert_near(look(&app, entity), 0.0);
+
+    // Left how the exit ended rather than popping back.
+    update(&mut app, 3);
+    assert_near(look(&app, entity), 0.0);
+  }
+
+  #[test]
+  fn fade_windows() {
+    check_windows(TransitionKind::Fade, alpha, 0.5);
+  }
+
+  #[test]
+  fn scale_windows() {
+    check_windows(TransitionKind::Scale, scale, 2.0);
+  }
+
+  #[test]
+  fn hidden_entities_are_put_back() {
+    let mut app = app();
+    let entity = spawn(
+      &mut app,
+      expire(TransitionKind::Fade).with_action(ExpireAction::Hide),
+    );
+    update(&mut app, 11);
+
+    assert_eq!(
+      app.world().get::<Visibility>(entity),
+      Some(&Visibility::Hidden)
+    );
+    assert_near(alpha(&app, entity), 0.5);
+  }
+
+  #[test]
+  fn new_expire_starts_from_the_original_look() {
+    let mut app = app();
+    let entity = spawn(&mut app, expire(TransitionKind::Scale));
+    update(&mut app, 11);
+    assert_near(scale(&app, entity), 0.0);
+
+    app
+      .world_mut()
+      .entity_mut(entity)
+      .insert(expire(TransitionKind::Scale));
+    update(&mut app, 5);
+    assert_near(scale(&app, entity), 2.0);
+
+    update(&mut app, 6);
+    app.world_mut().entity_mut(entity).insert(Expire::new(1.0));
+    assert_near(scale(&app, entity), 2.0);
+  }
+
+  #[test]
+  fn despawning_puts_shared_materials_back() {
+    let mut app = app();
+    let material = app
+      .world_mut()
+      .resource_mut::<Assets<ColorMaterial>>()
+      .add(Color::srgba(1.0, 0.0, 0.0, 0.8));
+    let entity = app
+      .world_mut()
+      .spawn((
+        MeshMaterial2d(material.clone()),
+        expire(TransitionKind::Fade).with_action(ExpireAction::Despawn),
+      ))
+      .id();
+    let material_alpha = |app: &App| {
+      let materials = app.world().resource::<Assets<ColorMaterial>>();
+      materials.get(&material).unwrap().color.alpha()
+    };
+
+    update(&mut app, 9);
+    assert_near(material_alpha(&app), 0.4);
+    update(&mut app, 2);
+    assert!(app.world().get_entity(entity).is_err());
+    assert_near(material_alpha(&app), 0.8);
+  }
+}
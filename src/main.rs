@@ -1,9 +1,9 @@
 use std::{io::Read, net::TcpListener, process::Command, time::Duration};
 
-use ::twitcheventsub::prelude::TwitchEvent;
+use ::twitcheventsub::prelude::{Tier, TwitchEvent};
 use bevy::{
   color::palettes::{
-    css::{BLACK, BLUE, WHITE},
+    css::{BLACK, BLUE, ORANGE, RED, WHITE},
     tailwind::{BLUE_400, RED_400, YELLOW_400},
   },
   core_pipeline::{bloom::Bloom, core_2d::graph::Node2d, tonemapping::Tonemapping},
@@ -19,8 +19,20 @@ use bevy::{
   sprite::{AlphaMode2d, Material2d, Material2dPlugin},
 };
 use bevy_tunnel::{ConnectTunnel, TunnelEvent};
-use clock::{AddTime, Clock, MakeClock};
-use draggable_interface::DraggableInterface;
+use clock::{
+  subathon::{SubTier, SubathonRules, Support, SupportKind},
+  AddTime, Clock, ClockChatMessage, ClockFinishedAction, ClockId, ClockTarget, ClockUrgency,
+  MakeClock, MultiplierKind, MultiplierWindow, PauseClock, ResumeClock, ScheduleMultiplier,
+  TimePopups, UrgencyStyle,
+};
+use draggable_interface::{
+  arrange::ReorderSelected,
+  constraints::{DragBounds, ResetSelected},
+  mode::{OverlayMode, OverlayModeEntered, ToggleOverlayMode},
+  profiles::{LayoutProfiles, SwitchLayoutProfile},
+  selection::{AlignSelected, DistributeSelected},
+  DraggableInterface,
+};
 use enable_disable_button::MakeToggleButton;
 use particles::{fireworks::CreateFireworks, PARTICLE_LAYER, UI_LAYER};
 use twitcheventsub::ManageTwitch;
@@ -47,6 +59,16 @@ const SHADER_ASSET_PATH: &str = "animate_shader.wgsl";
 const VORTEX_SHADER_ASSET_PATH: &str = "vortex.wgsl";
 const ADHD_SHADER_ASSET_PATH: &str = "adhd.wgsl";
 
+const JUST_CHATTING_PROFILE: &str = "Just Chatting";
+const GAMING_PROFILE: &str = "Gaming";
+const PROFILE_TWEEN_SECONDS: f32 = 0.5;
+
+const SUBATHON_CLOCK: &str = "subathon";
+/// How long the H key doubles contributions for.
+const HAPPY_HOUR_SECONDS: f32 = 3600.0;
+/// Who gifts and cheers are from when the supporter chose to hide it.
+const ANONYMOUS_SUPPORTER: &str = "Anonymous";
+
 #[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
 struct ADHDMaterial {}
 
@@ -119,7 +141,10 @@ fn main() {
       bevy_tunnel::plugin,
     ))
     .add_event::<TwitchEvent>()
-    .add_systems(Startup, setup)
+    .insert_resource(subathon_rules())
+    .add_observer(show_buttons_in_edit_mode)
+    .add_observer(send_clock_chat_message)
+    .add_systems(Startup, (setup, bind_layout_profiles))
     .add_systems(
       Update,
       (
@@ -127,6 +152,7 @@ fn main() {
         handle_twitch,
         handle_kofi,
         handle_ad_break,
+        send_twitch_support,
         spawn_fireworks,
       ),
     );
@@ -158,13 +184,11 @@ fn setup(
       overflow: Overflow::clip(),
       ..default()
     },
-    Pickable::IGNORE,
     Visibility::Hidden,
-    //Transform::from_translation(Vec3::new(50.0, 50.0, 0.0)),
-    //Mesh2d(meshes.add(Rectangle::new(chat_box_width, chat_box_height))),
-    //MeshMaterial2d(materials.add(ColorMaterial::from_color(WHITE))),
     BackgroundColor(Color::WHITE),
-    //DraggableInterface::new().with_scale_factor(1.0 / chat_box_width),
+    DraggableInterface::new()
+      .with_id("chat_box")
+      .with_scale_factor(0.1),
     children![
       (Node::default(), Text::new("Chat")),
       (
@@ -296,7 +320,9 @@ fn setup(
     ))),
     MeshMaterial2d(materials.add(ColorMaterial::from_color(YELLOW_400))),
     Transform::from_translation(Vec3::splat(50.0)).with_scale(Vec3::splat(50.0)),
-    DraggableInterface::new(),
+    DraggableInterface::new()
+      .with_id("triangle")
+      .with_bounds(DragBounds::Window),
   ));
 
   let mut progress_bar_texture = assets.load("./progress_bar_inside.png");
@@ -305,7 +331,10 @@ fn setup(
       Mesh2d(meshes.add(Rectangle::new(50.0, 50.0))),
       MeshMaterial2d(materials.add(ColorMaterial::from(assets.load("./progress_bar_empty.png")))),
       Transform::from_translation(Vec3::new(50.0, 50.0, 0.0)),
-      DraggableInterface::new().with_scale_factor(1.0 / 50.0),
+      DraggableInterface::new()
+        .with_id("progress_bar")
+        .with_bounds(DragBounds::Window)
+        .with_scale_factor(1.0 / 50.0),
       Visibility::Hidden,
     ))
     .with_child((
@@ -323,7 +352,10 @@ fn setup(
     MeshMaterial2d(vortex_materials.add(VortexMaterial {})),
     //MeshMaterial2d(materials.add(ColorMaterial::from_color(BLUE_400))),
     Transform::from_translation(Vec3::splat(-50.0)),
-    DraggableInterface::new().with_scale_factor(1.0 / 50.0),
+    DraggableInterface::new()
+      .with_id("vortex")
+      .with_bounds(DragBounds::Window)
+      .with_scale_factor(1.0 / 50.0),
     Visibility::Hidden,
   ));
 
@@ -332,14 +364,32 @@ fn setup(
     MeshMaterial2d(adhd_material.add(ADHDMaterial {})),
     //MeshMaterial2d(materials.add(ColorMaterial::from_color(BLUE_400))),
     Transform::from_translation(Vec3::splat(80.0)),
-    DraggableInterface::new().with_scale_factor(1.0 / 50.0),
+    DraggableInterface::new()
+      .with_id("adhd")
+      .with_bounds(DragBounds::Window)
+      .with_scale_factor(1.0 / 50.0),
     Visibility::Hidden,
   ));
 
   commands.spawn((
-    MakeClock(Clock::new(120.0)),
+    MakeClock::new(
+      Clock::new(120.0)
+        .with_real_time()
+        .with_finished_action(ClockFinishedAction::Fireworks(15.0))
+        .with_finished_action(ClockFinishedAction::Overtime),
+    )
+    .with_id(SUBATHON_CLOCK)
+    .with_popups(TimePopups::new())
+    .with_urgency(
+      ClockUrgency::new()
+        .with_style(UrgencyStyle::below(300.0).with_color(ORANGE))
+        .with_style(UrgencyStyle::below(60.0).with_color(RED).with_pulse(0.1)),
+    ),
     Transform::from_translation(Vec3::ZERO),
-    DraggableInterface::new().with_scale_factor(0.25),
+    DraggableInterface::new()
+      .with_id("clock")
+      .with_bounds(DragBounds::Window)
+      .with_scale_factor(0.25),
   ));
 
   commands
@@ -395,6 +445,94 @@ fn setup(
         ))
         .observe(trigger_event_on_click(CreateFireworks::new(15.0)))
         .with_child(Text::new("Fireworks!!!"));
+
+      parent
+        .spawn((
+          Node {
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+          },
+          BackgroundColor(RED_400.into()),
+        ))
+        .observe(trigger_event_on_click(AlignSelected::Left))
+        .with_child(Text::new("Align Left"));
+
+      parent
+        .spawn((
+          Node {
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+          },
+          BackgroundColor(RED_400.into()),
+        ))
+        .observe(trigger_event_on_click(DistributeSelected::Horizontally))
+        .with_child(Text::new("Distribute"));
+
+      parent
+        .spawn((
+          Node {
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+          },
+          BackgroundColor(RED_400.into()),
+        ))
+        .observe(trigger_event_on_click(ReorderSelected::BringToFront))
+        .with_child(Text::new("Bring To Front"));
+
+      parent
+        .spawn((
+          Node {
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+          },
+          BackgroundColor(RED_400.into()),
+        ))
+        .observe(trigger_event_on_click(ReorderSelected::SendToBack))
+        .with_child(Text::new("Send To Back"));
+
+      parent
+        .spawn((
+          Node {
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+          },
+          BackgroundColor(RED_400.into()),
+        ))
+        .observe(trigger_event_on_click(ResetSelected))
+        .with_child(Text::new("Reset Position"));
+
+      parent
+        .spawn((
+          Node {
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+          },
+          BackgroundColor(RED_400.into()),
+        ))
+        .observe(trigger_event_on_click(
+          SwitchLayoutProfile::new(JUST_CHATTING_PROFILE).with_tween(PROFILE_TWEEN_SECONDS),
+        ))
+        .with_child(Text::new("Just Chatting"));
+
+      parent
+        .spawn((
+          Node {
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+          },
+          BackgroundColor(RED_400.into()),
+        ))
+        .observe(trigger_event_on_click(
+          SwitchLayoutProfile::new(GAMING_PROFILE).with_tween(PROFILE_TWEEN_SECONDS),
+        ))
+        .with_child(Text::new("Gaming"));
     });
 }
 
@@ -402,13 +540,76 @@ fn handle_ad_break(mut twitch_events: EventReader<TwitchEvent>, mut commands: Co
   for event in twitch_events.read() {
     match event {
       TwitchEvent::AdBreakBegin(ad_break) => {
-        commands.trigger(AddTime::new(ad_break.duration_seconds as f32));
+        commands.trigger(AddTime::for_clock(
+          SUBATHON_CLOCK,
+          ad_break.duration_seconds as f32,
+        ));
       }
       _ => {}
     }
   }
 }
 
+fn subathon_rules() -> SubathonRules {
+  SubathonRules::new(ClockTarget::Id(ClockId::new(SUBATHON_CLOCK)))
+    .with_follow(30.0)
+    .with_subscription(SubTier::One, 300.0)
+    .with_subscription(SubTier::Two, 600.0)
+    .with_subscription(SubTier::Three, 1500.0)
+    .with_gift_subscription(SubTier::One, 300.0)
+    .with_gift_subscription(SubTier::Two, 600.0)
+    .with_gift_subscription(SubTier::Three, 1500.0)
+    .with_bits(60.0)
+    .with_raid(1.0)
+    .with_donation(60.0)
+    .with_max_per_event(3600.0)
+    .with_cap(7.0 * 24.0 * 3600.0)
+}
+
+fn send_twitch_support(mut twitch_events: EventReader<TwitchEvent>, mut commands: Commands) {
+  for event in twitch_events.read() {
+    let support = match event {
+      TwitchEvent::Follow(follow) => Support::new(&follow.user.name, SupportKind::Follow),
+      // Gifted subscriptions are counted by the gift event instead.
+      TwitchEvent::NewSubscription(subscription) if !subscription.is_gift => Support::new(
+        &subscription.user.name,
+        SupportKind::Subscription(sub_tier(&subscription.tier)),
+      ),
+      TwitchEvent::Resubscription(resubscription) => Support::new(
+        &resubscription.user.name,
+        SupportKind::Subscription(sub_tier(&resubscription.tier)),
+      ),
+      TwitchEvent::GiftSubscription(gift) => Support::new(
+        gift.user.name.as_deref().unwrap_or(ANONYMOUS_SUPPORTER),
+        SupportKind::GiftSubscription {
+          tier: sub_tier(&gift.tier),
+          count: gift.total,
+        },
+      ),
+      TwitchEvent::Cheer(cheer) => Support::new(
+        cheer.user.name.as_deref().unwrap_or(ANONYMOUS_SUPPORTER),
+        SupportKind::Bits(cheer.bits),
+      ),
+      TwitchEvent::Raid(raid) => Support::new(
+        &raid.from_broadcaster.name,
+        SupportKind::Raid {
+          viewers: raid.viewers,
+        },
+      ),
+      _ => continue,
+    };
+    commands.trigger(support);
+  }
+}
+
+fn sub_tier(tier: &Tier) -> SubTier {
+  match tier {
+    Tier::Tier2 => SubTier::Two,
+    Tier::Tier3 => SubTier::Three,
+    _ => SubTier::One,
+  }
+}
+
 fn spawn_fireworks(mut twitch_events: EventReader<TwitchEvent>, mut commands: Commands) {
   for event in twitch_events.read() {
     match event {
@@ -447,6 +648,20 @@ fn handle_kofi(
           kofi_donation.from_name, kofi_donation.amount
         );
         commands.trigger(CreateFireworks::new(60.0));
+        match kofi_donation.amount.trim().parse::<f32>() {
+          Ok(amount) if amount.is_finite() && amount >= 0.0 => {
+            commands.trigger(Support::new(
+              &kofi_donation.from_name,
+              SupportKind::Donation(amount),
+            ));
+          }
+          _ => {
+            warn!(
+              "Not adding subathon time for {}'s donation, {:?} isn't an amount",
+              kofi_donation.from_name, kofi_donation.amount
+            );
+          }
+        }
       }
       TunnelEvent::Twitch(twitch_event) => {
         twitch_events.write(twitch_event.to_owned());
@@ -491,7 +706,6 @@ fn handle_twitch(
 
 fn input(
   buttons: Res<ButtonInput<KeyCode>>,
-  mut interactivity_layer: Query<&mut Visibility, With<InteractiveButtonsUi>>,
   mut progress_bar: Query<&MeshMaterial2d<CustomMaterial>, With<ProgressBar>>,
   mut custom_materials: ResMut<Assets<CustomMaterial>>,
   mut commands: Commands,
@@ -511,19 +725,66 @@ fn input(
   }
 
   if buttons.just_pressed(KeyCode::KeyP) {
-    commands.trigger(AddTime::new(10.0));
+    commands.trigger(AddTime::for_clock(SUBATHON_CLOCK, 10.0));
+  }
+  if buttons.just_pressed(KeyCode::KeyO) {
+    commands.trigger(AddTime::for_clock(SUBATHON_CLOCK, -10.0));
+  }
+  if buttons.just_pressed(KeyCode::KeyK) {
+    commands.trigger(PauseClock::for_clock(SUBATHON_CLOCK));
+  }
+  if buttons.just_pressed(KeyCode::KeyL) {
+    commands.trigger(ResumeClock::for_clock(SUBATHON_CLOCK));
+  }
+  if buttons.just_pressed(KeyCode::KeyH) {
+    commands.trigger(ScheduleMultiplier::for_clock(
+      SUBATHON_CLOCK,
+      MultiplierKind::Contribution,
+      MultiplierWindow::from_now(HAPPY_HOUR_SECONDS, 2.0),
+    ));
   }
+  // Live mode can't be clicked, focusing the overlay again with alt-tab or the
+  // taskbar is what brings it back to edit mode.
   if buttons.just_pressed(KeyCode::Space) {
-    for mut visibility in &mut interactivity_layer {
-      if visibility.eq(&Visibility::Visible) {
-        *visibility = Visibility::Hidden;
-      } else {
-        *visibility = Visibility::Visible;
-      }
-    }
+    commands.trigger(ToggleOverlayMode);
   }
 }
 
+/// F1 and F2 switch between scenes, Ctrl+F1 and Ctrl+F2 save the current
+/// layout as that scene.
+fn bind_layout_profiles(mut profiles: ResMut<LayoutProfiles>) {
+  profiles.bind(
+    KeyCode::F1,
+    SwitchLayoutProfile::new(JUST_CHATTING_PROFILE).with_tween(PROFILE_TWEEN_SECONDS),
+  );
+  profiles.bind(
+    KeyCode::F2,
+    SwitchLayoutProfile::new(GAMING_PROFILE).with_tween(PROFILE_TWEEN_SECONDS),
+  );
+}
+
+fn show_buttons_in_edit_mode(
+  trigger: Trigger<OverlayModeEntered>,
+  mut interactivity_layer: Query<&mut Visibility, With<InteractiveButtonsUi>>,
+) {
+  if trigger.0 == OverlayMode::Live {
+    info!("Overlay is live, focus it again with alt-tab or the taskbar to edit it");
+  }
+  for mut visibility in &mut interactivity_layer {
+    *visibility = match trigger.0 {
+      OverlayMode::Edit => Visibility::Visible,
+      OverlayMode::Live => Visibility::Hidden,
+    };
+  }
+}
+
+fn send_clock_chat_message(
+  trigger: Trigger<ClockChatMessage>,
+  mut twitch_manager: EventWriter<ManageTwitch>,
+) {
+  twitch_manager.write(ManageTwitch::SendChatMsg(trigger.0.clone()));
+}
+
 fn send_event_on_click<E: Event + Clone>(
   event: E,
 ) -> impl Fn(Trigger<Pointer<Pressed>>, EventWriter<E>) {